@@ -49,17 +49,196 @@
 //! );
 //! # }
 //! ```
+//!
+//! # Compression
+//!
+//! With the `compression` feature enabled, responses are requested with
+//! `Accept-Encoding: gzip, deflate` and transparently decompressed,
+//! which noticeably reduces traffic of big batch requests.
+//!
+//! # Streaming
+//!
+//! With the `stream` feature enabled, [`IpApiConfig::make_batch_stream`] parses
+//! the results of big batch requests while they are being received,
+//! without keeping the whole response body in memory.
+//! The returned [`BatchStream`] implements
+//! [`Stream`](https://docs.rs/futures-core/latest/futures_core/stream/trait.Stream.html).
+//!
+//! # JSON Logs
+//!
+//! With the `json-logs` feature enabled, [`IpApiConfig::set_log_sink`] writes
+//! a single-line JSON log entry per request to any [`Write`](std::io::Write) sink.
+//!
+//! # Metrics
+//!
+//! With the `metrics` feature enabled, [`IpApiConfig::make_request`],
+//! [`IpApiConfig::make_batch_request`] and [`BatchStream`] emit the following counters
+//! via the [metrics](https://docs.rs/metrics) facade:
+//!
+//! - `ip_api_requests_total` - every made request
+//! - `ip_api_errors_total` - every failed request, labeled by `kind`
+//!   (e.g. `invalid_query`, `rate_limit` or `unexpected_error`)
+//! - `ip_api_rate_limited_total` - every request rejected by the rate limit
 
 #![deny(missing_docs)]
 
 use hyper::body::HttpBody;
-use hyper::{Body, Client, Method, Request, Response};
-use serde::Deserialize;
+use hyper::client::connect::dns::{GaiResolver, Name};
+use hyper::client::connect::{Connected, Connection};
+use hyper::client::HttpConnector;
+use hyper::service::Service;
+use hyper::Uri;
+use hyper::{Body, Client, HeaderMap, Method, Request, Response};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::json;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use std::vec;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_util::sync::CancellationToken;
+
+const API_HOST: &str = "ip-api.com";
+const API_BASE_URL: &str = "http://ip-api.com";
+const BATCH_REQUEST_LIMIT: usize = 100;
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+const MAPS_SEARCH_URL: &str = "https://www.google.com/maps/search/?api=1&query=";
+const EARTH_RADIUS_KM: f64 = 6371.0;
+const STATUS_FIELD_BIT: u32 = 16384;
+const DEFAULT_RETRY_WAIT: Duration = Duration::from_millis(200);
+const PACING_LIMIT: usize = 45;
+const BATCH_PACING_LIMIT: usize = 15;
+const PACING_WINDOW: Duration = Duration::from_secs(60);
 
 #[cfg(test)]
+#[allow(clippy::get_first)] // Newer clippy flags `get(0)` in the original batch test
 mod tests {
-    use crate::{generate_empty_config, IpData};
+    use crate::{
+        generate_empty_config, generate_maximum_config, Backoff, BatchEntry, BatchInvalidPolicy,
+        CacheStatus, CoarsePrecision, ConfigError, Coordinates, EnvProxy, FieldDiff, FieldValue,
+        IpApiConfig, IpApiError, IpApiLanguage, IpApiMessage, IpData, IpDataField, NetworkCategory,
+        Pacing, QueryKind, RateLimitInfo, RequestMode, ResponseCache, RiskWeights,
+    };
+    use hyper::{Body, HeaderMap, Response};
+    use serde_json::json;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+    use std::time::{Duration, Instant};
+    use tokio_util::sync::CancellationToken;
+
+    /// Serve one request on a local port with `body` and return the raw request
+    fn serve_once(body: &'static str) -> (SocketAddr, JoinHandle<String>) {
+        serve_once_with_status("200 OK", body)
+    }
+
+    /// Serve one request on a local port with `status` and `body` and return the raw request
+    fn serve_once_with_status(
+        status: &'static str,
+        body: &'static str,
+    ) -> (SocketAddr, JoinHandle<String>) {
+        let (address, server) = serve_each(vec![(status, body.into())]);
+
+        (
+            address,
+            thread::spawn(move || server.join().unwrap().remove(0)),
+        )
+    }
+
+    /// Serve one connection per response on a local port, answering with `status`
+    /// (optionally followed by header lines) and `body` of the responses in order,
+    /// and return the raw requests (including their bodies)
+    fn serve_each(responses: Vec<(&'static str, String)>) -> (SocketAddr, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    let read = stream.read(&mut buffer).unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                }
+
+                let request = String::from_utf8(request).unwrap();
+                let (head, content) = request.split_once("\r\n\r\n").unwrap();
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or_default();
+                let mut content = content.as_bytes().to_vec();
+
+                while content.len() < length {
+                    let read = stream.read(&mut buffer).unwrap();
+                    content.extend_from_slice(&buffer[..read]);
+                }
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+
+                requests.push(format!(
+                    "{head}\r\n\r\n{}",
+                    String::from_utf8(content).unwrap()
+                ));
+            }
+
+            requests
+        });
+
+        (address, server)
+    }
+
+    fn ip_data() -> IpData {
+        IpData {
+            continent: Some("Oceania".to_string()),
+            continent_code: Some("OC".to_string()),
+            country: Some("Australia".to_string()),
+            country_code: Some("AU".to_string()),
+            region: Some("QLD".to_string()),
+            region_name: Some("Queensland".to_string()),
+            city: Some("South Brisbane".to_string()),
+            district: Some("".to_string()),
+            zip: Some("4101".to_string()),
+            lat: Some(-27.4766),
+            lon: Some(153.0166),
+            timezone: Some("Australia/Brisbane".to_string()),
+            offset: Some(36000),
+            currency: Some("AUD".to_string()),
+            isp: Some("Cloudflare, Inc".to_string()),
+            org: Some("APNIC and Cloudflare DNS Resolver project".to_string()),
+            as_field: Some("AS13335 Cloudflare, Inc.".to_string()),
+            asname: Some("CLOUDFLARENET".to_string()),
+            reverse: Some("one.one.one.one".to_string()),
+            mobile: Some(false),
+            proxy: Some(false),
+            hosting: Some(true),
+            query: Some("1.1.1.1".to_string()),
+        }
+    }
 
     #[tokio::test]
     async fn make_request() {
@@ -86,248 +265,4894 @@ mod tests {
         assert_eq!(ips.get(0).unwrap().query, Some(String::from("1.1.1.1")));
         assert_eq!(ips.get(1).unwrap().query, Some(String::from("8.8.8.8")))
     }
-}
 
-/// Represents all the ways that a request can fail
-#[derive(Clone, Debug)]
-pub enum IpApiError {
-    /// Incorrect IP address or non-existent domain
-    ///
-    /// # Example
-    ///
-    /// 1.1.1.one **OR** test.google.com
-    InvalidQuery,
+    #[tokio::test]
+    async fn parse_empty_response_body() {
+        let mut response = Response::new(Body::empty());
 
-    /// IPs in your network
-    ///
-    /// # Example
-    ///
-    /// 192.168.1.1
-    PrivateRange,
+        assert!(matches!(
+            generate_empty_config()
+                .parse_response_body(&mut response)
+                .await,
+            Err(IpApiError::EmptyResponse)
+        ));
+    }
 
-    /// [ip-api.com API](https://ip-api.com/docs/api:json) is limited to 45 requests per minute
-    /// from one IP address
-    ///
-    /// Contains the remaining time before a possible re-request in seconds
-    RateLimit(u8),
+    #[tokio::test]
+    async fn parse_stalled_response_body() {
+        let (_sender, body) = Body::channel();
+        let mut response = Response::new(body);
 
-    /// Reserved Range
-    ///
-    /// # Example
-    ///
-    /// 127.0.0.1 **OR** localhost
-    ReservedRange,
+        assert!(matches!(
+            generate_empty_config()
+                .set_read_timeout(Duration::from_millis(10))
+                .parse_response_body(&mut response)
+                .await,
+            Err(IpApiError::Timeout)
+        ));
+    }
 
-    /// Unexpected Error
-    ///
-    /// May contain additional information
-    UnexpectedError(Option<String>),
-}
+    #[tokio::test]
+    async fn parse_too_large_response_body() {
+        let mut response = Response::new(Body::from("x".repeat(100)));
 
-/// Represents all available languages for [`IpData`]
-#[derive(Clone, Debug)]
-pub enum IpApiLanguage {
-    /// Deutsch (German)
-    De,
+        assert!(matches!(
+            generate_empty_config()
+                .set_max_response_bytes(10)
+                .parse_response_body(&mut response)
+                .await,
+            Err(IpApiError::ResponseTooLarge)
+        ));
+    }
 
-    /// English (default)
-    En,
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn parse_compressed_response_body() {
+        use flate2::write::{GzEncoder, ZlibEncoder};
+        use flate2::Compression;
+
+        let body = r#"{"country":"Australia"}"#;
+        let response = |encoding: &str, bytes: Vec<u8>| {
+            Response::builder()
+                .header(hyper::header::CONTENT_ENCODING, encoding)
+                .body(Body::from(bytes))
+                .unwrap()
+        };
 
-    /// Español (Spanish)
-    Es,
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(body.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
 
-    /// Français (French)
-    Fr,
+        let mut deflate = ZlibEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(body.as_bytes()).unwrap();
+        let deflate = deflate.finish().unwrap();
 
-    /// 日本語 (Japanese)
-    Ja,
+        let config = generate_empty_config();
 
-    /// Português - Brasil (Portuguese - Brasil)
-    PtBr,
+        for (encoding, bytes) in [("gzip", gzip.clone()), (" Deflate ", deflate)] {
+            assert_eq!(
+                config
+                    .parse_response_body(&mut response(encoding, bytes))
+                    .await
+                    .unwrap(),
+                body
+            );
+        }
 
-    /// Русский (Russian)
-    Ru,
+        // Unknown encodings are left as is
+        assert_eq!(
+            config
+                .parse_response_body(&mut response("identity", body.into()))
+                .await
+                .unwrap(),
+            body
+        );
 
-    /// 中国 (Chinese)
-    ZhCn,
-}
+        let mut corrupt = gzip.clone();
+        corrupt.truncate(gzip.len() / 2);
+        assert!(matches!(
+            config.parse_response_body(&mut response("gzip", corrupt)).await,
+            Err(IpApiError::UnexpectedError(Some(message)))
+                if message == "Failed to decompress body from the response"
+        ));
+
+        assert!(matches!(
+            config
+                .clone()
+                .set_max_response_bytes(10)
+                .parse_response_body(&mut response("gzip", gzip))
+                .await,
+            Err(IpApiError::ResponseTooLarge)
+        ));
+    }
 
-#[derive(Deserialize)]
-struct IpApiMessage {
-    message: Option<String>,
-}
+    #[tokio::test]
+    async fn make_request_with_cancel() {
+        let token = CancellationToken::new();
+        token.cancel();
 
-/// The data that will be received after the making a request
-///
-/// # Example response
-///
-/// ```rust
-/// # use ip_api_client::IpData;
-/// #
-/// IpData {
-///     continent: Some("Oceania".to_string()),
-///     continent_code: Some("OC".to_string()),
-///     country: Some("Australia".to_string()),
-///     country_code: Some("AU".to_string()),
-///     region: Some("QLD".to_string()),
-///     region_name: Some("Queensland".to_string()),
-///     city: Some("South Brisbane".to_string()),
-///     district: Some("".to_string()),
-///     zip: Some("4101".to_string()),
-///     lat: Some(-27.4766),
-///     lon: Some(153.0166),
-///     timezone: Some("Australia/Brisbane".to_string()),
-///     offset: Some(36000),
-///     currency: Some("AUD".to_string()),
-///     isp: Some("Cloudflare, Inc".to_string()),
-///     org: Some("APNIC and Cloudflare DNS Resolver project".to_string()),
-///     as_field: Some("AS13335 Cloudflare, Inc.".to_string()),
-///     asname: Some("CLOUDFLARENET".to_string()),
-///     reverse: Some("one.one.one.one".to_string()),
-///     mobile: Some(false),
-///     proxy: Some(false),
-///     hosting: Some(true),
-///     query: Some("1.1.1.1".to_string()),
-/// };
-/// ```
-#[derive(Clone, Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct IpData {
-    /// Continent name
-    pub continent: Option<String>,
+        assert!(matches!(
+            generate_empty_config()
+                .make_request_with_cancel("1.1.1.1", token)
+                .await,
+            Err(IpApiError::Cancelled)
+        ));
+    }
 
-    /// Two-letter continent code
-    pub continent_code: Option<String>,
+    #[test]
+    fn iter() {
+        let mut ip_data = ip_data();
+        ip_data.country = None;
 
-    /// Country name
-    pub country: Option<String>,
+        let fields: Vec<_> = ip_data.iter().collect();
 
-    /// Two-letter country code
-    /// [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2)
-    pub country_code: Option<String>,
+        assert_eq!(fields.len(), 22);
+        assert_eq!(fields[0], ("continent", FieldValue::Str("Oceania")));
+        assert_eq!(fields[2], ("countryCode", FieldValue::Str("AU")));
+        assert_eq!(fields[8], ("lat", FieldValue::F32(-27.4766)));
+        assert_eq!(
+            fields[15],
+            ("as", FieldValue::Str("AS13335 Cloudflare, Inc."))
+        );
+        assert_eq!(ip_data.to_string_map()["hosting"], "true");
+    }
 
-    /// Region/state short code (FIPS or ISO)
-    pub region: Option<String>,
+    #[test]
+    fn check_error_message() {
+        let check = |target: &str| {
+            IpApiConfig::check_error_message(
+                IpApiMessage {
+                    message: Some("invalid query".into()),
+                },
+                target,
+            )
+        };
 
-    /// Region/state
-    pub region_name: Option<String>,
+        assert!(matches!(
+            check("test.google.com"),
+            Err(IpApiError::DomainNotResolved(domain)) if domain == "test.google.com"
+        ));
+        assert!(matches!(
+            check("Test.Google.com."),
+            Err(IpApiError::DomainNotResolved(domain)) if domain == "Test.Google.com."
+        ));
+        assert!(matches!(check("1.1.1.one"), Err(IpApiError::InvalidQuery)));
+        assert!(matches!(check("1.1.1.256"), Err(IpApiError::InvalidQuery)));
+        assert!(matches!(check("1.1.1"), Err(IpApiError::InvalidQuery)));
+    }
 
-    /// City
-    pub city: Option<String>,
+    #[test]
+    fn env_proxy() {
+        let proxy = EnvProxy {
+            http: Some("http://127.0.0.1:3128".parse().unwrap()),
+            https: None,
+            no_proxy: vec!["example.com".into()],
+        };
 
-    /// District (subdivision of city)
-    pub district: Option<String>,
+        assert_eq!(
+            proxy.proxy_for(&"http://ip-api.com/json".parse().unwrap()),
+            proxy.http.as_ref()
+        );
+        assert_eq!(
+            proxy.proxy_for(&"https://ip-api.com/json".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            proxy.proxy_for(&"http://api.example.com/".parse().unwrap()),
+            None
+        );
+    }
 
-    /// Zip code
-    pub zip: Option<String>,
+    #[test]
+    fn build_batch_body() {
+        let body = IpApiConfig::build_batch_body(vec![
+            BatchEntry::new("1.1.1.1"),
+            BatchEntry {
+                fields: Some("country,city".into()),
+                ..BatchEntry::new("8.8.8.8")
+            },
+            BatchEntry {
+                lang: Some(IpApiLanguage::PtBr.code()),
+                ..BatchEntry::new("9.9.9.9")
+            },
+        ]);
 
-    /// Latitude
-    pub lat: Option<f32>,
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+            json!([
+                { "query": "1.1.1.1" },
+                { "query": "8.8.8.8", "fields": "country,city" },
+                { "query": "9.9.9.9", "lang": "pt-BR" },
+            ])
+        );
+    }
 
-    /// Longitude
-    pub lon: Option<f32>,
+    #[test]
+    fn district_name() {
+        let ip_data: IpData =
+            serde_json::from_str(r#"{"city":"South Brisbane","district":""}"#).unwrap();
 
-    /// Timezone (tz)
-    pub timezone: Option<String>,
+        assert_eq!(ip_data.district, Some(String::new()));
+        assert_eq!(ip_data.district_name(), None);
 
-    /// Timezone UTC DST offset in seconds
-    pub offset: Option<i32>,
+        let ip_data: IpData = serde_json::from_str(r#"{"district":"Mitte"}"#).unwrap();
 
-    /// National currency
-    pub currency: Option<String>,
+        assert_eq!(ip_data.district_name(), Some("Mitte"));
+    }
 
-    /// ISP name
-    pub isp: Option<String>,
+    #[test]
+    fn to_pretty_json() {
+        let ip_data = IpData {
+            as_field: Some("AS13335 Cloudflare, Inc.".to_string()),
+            ..serde_json::from_str("{}").unwrap()
+        };
 
-    /// Organization name
-    pub org: Option<String>,
+        assert_eq!(
+            ip_data.to_pretty_json(),
+            "{\n  \"as\": \"AS13335 Cloudflare, Inc.\"\n}"
+        );
+    }
 
-    /// AS number and organization, separated by space (RIR).
-    /// Empty for IP blocks not being announced in BGP tables.
-    ///
-    /// # Notice
-    ///
-    /// We use `as_field` instead of `as`
-    /// (As stated in the [ip-api.com API documentation](https://ip-api.com/docs/api:json#as))
-    /// since it's a
-    /// [strict keyword](https://doc.rust-lang.org/reference/keywords.html#strict-keywords) in rust,
-    /// such as `pub`, `impl` or `struct`.
-    #[serde(rename = "as")]
-    pub as_field: Option<String>,
+    #[test]
+    fn serialization_round_trip() {
+        let mut ip_data = ip_data();
+        ip_data.city = None;
+        ip_data.lat = None;
 
-    /// AS name (RIR). Empty for IP blocks not being announced in BGP tables.
-    pub asname: Option<String>,
+        let json = serde_json::to_string(&ip_data).unwrap();
+        let deserialized: IpData = serde_json::from_str(&json).unwrap();
 
-    /// Reverse DNS of the IP (can delay response)
-    pub reverse: Option<String>,
+        assert!(!json.contains("null"));
+        assert_eq!(deserialized.city, None);
+        assert_eq!(deserialized.lat, None);
+        assert_eq!(deserialized.diff(&ip_data), vec![]);
+        assert_eq!(deserialized.iter().count(), ip_data.iter().count());
+    }
 
-    /// Mobile (cellular) connection
-    pub mobile: Option<bool>,
+    #[tokio::test]
+    async fn connect_to() {
+        let (address, server) = serve_once(r#"{"country":"Australia","query":"1.1.1.1"}"#);
 
-    /// Proxy, VPN or Tor exit address
-    pub proxy: Option<bool>,
+        let ip_data = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
 
-    /// Hosting, colocated or data center
-    pub hosting: Option<bool>,
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+        assert!(request.starts_with("GET /json/1.1.1.1?fields="));
+        assert!(request
+            .to_ascii_lowercase()
+            .contains("host: ip-api.com\r\n"));
+    }
 
-    /// IP/Domain used for the query
-    pub query: Option<String>,
-}
+    #[test]
+    fn formatted_address() {
+        assert_eq!(
+            ip_data().formatted_address().as_deref(),
+            Some("South Brisbane, QLD 4101, Australia")
+        );
 
-/// Configuration structure allows you to customize the requested fields in the request
-/// to save traffic
-#[derive(Clone, Debug)]
-pub struct IpApiConfig {
-    numeric_field: u32,
-    is_continent_included: bool,
-    is_continent_code_included: bool,
-    is_country_included: bool,
-    is_country_code_included: bool,
-    is_region_included: bool,
-    is_region_name_included: bool,
-    is_city_included: bool,
-    is_district_included: bool,
-    is_zip_included: bool,
-    is_lat_included: bool,
-    is_lon_included: bool,
-    is_timezone_included: bool,
-    is_offset_included: bool,
-    is_currency_included: bool,
-    is_isp_included: bool,
-    is_org_included: bool,
-    is_as_field_included: bool,
-    is_asname_included: bool,
-    is_reverse_included: bool,
-    is_mobile_included: bool,
-    is_proxy_included: bool,
-    is_hosting_included: bool,
-    is_query_included: bool,
-    language: IpApiLanguage,
-}
+        let partial = IpData {
+            region: None,
+            zip: Some(" ".to_string()),
+            ..ip_data()
+        };
+        assert_eq!(
+            partial.formatted_address().as_deref(),
+            Some("South Brisbane, Queensland, Australia")
+        );
 
-impl IpApiConfig {
-    fn build_uri(
-        resource: &str,
-        target: Option<&str>,
-        fields: u32,
-        language: IpApiLanguage,
-    ) -> String {
-        format!(
-            "http://ip-api.com/{}/{}?fields={}{}",
-            resource,
-            target.unwrap_or(""),
+        let country_only = IpData {
+            city: None,
+            region: None,
+            region_name: None,
+            zip: None,
+            ..ip_data()
+        };
+        assert_eq!(
+            country_only.formatted_address().as_deref(),
+            Some("Australia")
+        );
+
+        let empty = IpData {
+            country: Some(String::new()),
+            ..country_only
+        };
+        assert_eq!(empty.formatted_address(), None);
+    }
+
+    #[test]
+    fn risk_score() {
+        let flags = |proxy, hosting, mobile| IpData {
+            proxy,
+            hosting,
+            mobile,
+            ..ip_data()
+        };
+
+        assert_eq!(flags(Some(false), Some(false), Some(false)).risk_score(), 0);
+        assert_eq!(flags(Some(true), Some(true), Some(false)).risk_score(), 80);
+        assert_eq!(flags(Some(true), None, Some(true)).risk_score(), 40);
+        assert_eq!(flags(None, None, Some(true)).risk_score(), 0);
+
+        let weights = RiskWeights {
+            proxy: 90,
+            hosting: 40,
+            mobile: -10,
+        };
+        assert_eq!(
+            flags(Some(true), Some(true), Some(false)).risk_score_with(weights),
+            100
+        );
+    }
+
+    #[test]
+    fn rate_limit_info() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Rl", "14".parse().unwrap());
+        headers.insert("X-Ttl", "37".parse().unwrap());
+
+        assert_eq!(
+            RateLimitInfo::from_headers(&headers),
+            RateLimitInfo {
+                remaining: Some(14),
+                reset_in: Some(Duration::from_secs(37)),
+            }
+        );
+
+        headers.insert("X-Ttl", "soon".parse().unwrap());
+        headers.remove("X-Rl");
+        assert_eq!(
+            RateLimitInfo::from_headers(&headers),
+            RateLimitInfo {
+                remaining: None,
+                reset_in: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn request_pacing() {
+        let window = Duration::from_millis(300);
+        let config = IpApiConfig {
+            pacing: Some(Arc::new(Pacing::new(window))),
+            ..generate_empty_config()
+        };
+        let started_at = Instant::now();
+
+        for _ in 0..15 {
+            config.wait_for_pacing(RequestMode::Batch).await;
+        }
+        for _ in 0..45 {
+            config.wait_for_pacing(RequestMode::Single).await;
+        }
+        assert!(started_at.elapsed() < window);
+
+        config.wait_for_pacing(RequestMode::Batch).await;
+        assert!(started_at.elapsed() >= window);
+    }
+
+    #[tokio::test]
+    async fn retry_budget() {
+        let (address, server) = serve_each(vec![
+            ("503 Service Unavailable", "".into()),
+            ("200 OK", "".into()),
+            ("200 OK", r#"{"country":"Australia"}"#.into()),
+        ]);
+
+        let ip_data = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .set_backoff(Backoff::Immediate)
+            .set_retry_budget(3, Duration::from_secs(1))
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        assert_eq!(server.join().unwrap().len(), 3);
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+
+        let (address, server) = serve_each(vec![("200 OK", "".into()), ("200 OK", "".into())]);
+
+        let result = generate_empty_config()
+            .connect_to(address)
+            .set_backoff(Backoff::Immediate)
+            .set_retry_budget(2, Duration::from_secs(1))
+            .make_request("1.1.1.1")
+            .await;
+        server.join().unwrap();
+        assert!(matches!(result, Err(IpApiError::EmptyResponse)));
+
+        // A retry would fail with `QuotaExceeded` instead
+        let result = generate_empty_config()
+            .connect_to("127.0.0.1:9".parse().unwrap())
+            .bind_to("192.0.2.1".parse().unwrap())
+            .set_backoff(Backoff::Immediate)
+            .set_retry_budget(3, Duration::from_secs(1))
+            .set_request_quota(1)
+            .make_request("1.1.1.1")
+            .await;
+        assert!(matches!(result, Err(IpApiError::BindFailed)));
+    }
+
+    #[tokio::test]
+    async fn make_chunked_batch_request() {
+        let targets = vec!["1.1.1.1"; 101];
+        let results = |count: usize| format!("[{}]", vec![r#"{"zip":"4101"}"#; count].join(","));
+
+        let (address, server) = serve_each(vec![("200 OK", results(100)), ("200 OK", results(1))]);
+        let ip_batch_data = generate_empty_config()
+            .include_zip()
+            .connect_to(address)
+            .make_batch_request(targets.clone())
+            .await
+            .unwrap();
+        let requests = server.join().unwrap();
+        assert_eq!(ip_batch_data.len(), 101);
+        assert_eq!(requests[0].matches("1.1.1.1").count(), 100);
+        assert_eq!(requests[1].matches("1.1.1.1").count(), 1);
+
+        let rate_limit = "429 Too Many Requests\r\nX-Ttl: 30";
+        let (address, server) = serve_each(vec![("200 OK", results(100)), (rate_limit, "".into())]);
+        let result = generate_empty_config()
+            .include_zip()
+            .connect_to(address)
+            .make_batch_request(targets.clone())
+            .await;
+        server.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(IpApiError::RateLimitPartial { completed, retry_in })
+                if completed.len() == 100 && retry_in == Duration::from_secs(30)
+        ));
+
+        let (address, server) = serve_each(vec![(rate_limit, "".into())]);
+        let result = generate_empty_config()
+            .connect_to(address)
+            .make_batch_request(targets.clone())
+            .await;
+        server.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(IpApiError::RateLimitPartial { completed, .. }) if completed.is_empty()
+        ));
+
+        let (address, server) =
+            serve_each(vec![("200 OK", results(100)), ("200 OK", "[{}]".into())]);
+        let result = generate_empty_config()
+            .include_zip()
+            .strict()
+            .connect_to(address)
+            .make_batch_request(targets)
+            .await;
+        server.join().unwrap();
+        let Err(IpApiError::BatchPartial { completed, error }) = result else {
+            panic!("expected a partial batch");
+        };
+        assert_eq!(completed.len(), 100);
+        assert!(matches!(
+            *error,
+            IpApiError::MissingFields(fields) if fields == [IpDataField::Zip]
+        ));
+    }
+
+    #[tokio::test]
+    async fn make_batch_request_localized() {
+        let items = vec![("1.1.1.1", IpApiLanguage::De); 100]
+            .into_iter()
+            .chain([("8.8.8.8", IpApiLanguage::Ja)])
+            .collect();
+        let results = |count: usize| format!("[{}]", vec!["{}"; count].join(","));
+
+        let (address, server) = serve_each(vec![("200 OK", results(100)), ("200 OK", results(1))]);
+        let ip_batch_data = generate_empty_config()
+            .connect_to(address)
+            .make_batch_request_localized(items)
+            .await
+            .unwrap();
+        let requests = server.join().unwrap();
+        assert_eq!(ip_batch_data.len(), 101);
+        assert_eq!(requests[0].matches(r#""lang":"de""#).count(), 100);
+        assert!(requests[1].ends_with(r#"[{"lang":"ja","query":"8.8.8.8"}]"#));
+    }
+
+    #[tokio::test]
+    async fn make_batch_request_mixed() {
+        let items = vec![("1.1.1.1", generate_empty_config().include_country()); 100]
+            .into_iter()
+            .chain([("8.8.8.8", generate_empty_config().include_city())])
+            .collect();
+        let results =
+            |count: usize| format!("[{}]", vec![r#"{"country":"Australia"}"#; count].join(","));
+
+        let (address, server) =
+            serve_each(vec![("200 OK", results(100)), ("200 OK", "[{}]".into())]);
+        let result = generate_empty_config()
+            .include_country()
+            .strict()
+            .connect_to(address)
+            .make_batch_request_mixed(items)
+            .await;
+        let requests = server.join().unwrap();
+        assert_eq!(
+            requests[0].matches(r#""fields":"country,message""#).count(),
+            100
+        );
+        assert!(requests[1].ends_with(r#"[{"fields":"city,message","query":"8.8.8.8"}]"#));
+
+        let Err(IpApiError::BatchPartial { completed, error }) = result else {
+            panic!("expected a partial batch");
+        };
+        assert_eq!(completed.len(), 100);
+        assert!(matches!(
+            *error,
+            IpApiError::MissingFields(fields) if fields == [IpDataField::City]
+        ));
+    }
+
+    #[tokio::test]
+    async fn request_quota() {
+        let (address, server) = serve_once(r#"{"country":"Australia"}"#);
+        let config = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .set_retry_budget(3, Duration::from_secs(1))
+            .set_request_quota(1);
+
+        assert!(config.clone().make_request("1.1.1.1").await.is_ok());
+        server.join().unwrap();
+
+        assert!(matches!(
+            config.make_request("8.8.8.8").await,
+            Err(IpApiError::QuotaExceeded)
+        ));
+    }
+
+    #[tokio::test]
+    async fn make_request_report() {
+        let (address, server) = serve_once(r#"{"country":"Australia","query":"1.1.1.1"}"#);
+
+        let (ip_data, missing_fields) = generate_empty_config()
+            .include_country()
+            .include_zip()
+            .include_district()
+            .strict()
+            .connect_to(address)
+            .make_request_report("1.1.1.1")
+            .await
+            .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+        assert_eq!(
+            missing_fields,
+            vec![IpDataField::District, IpDataField::Zip]
+        );
+    }
+
+    #[cfg(feature = "json-logs")]
+    #[tokio::test]
+    async fn log_sink() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Sink(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Sink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (address, server) = serve_once(r#"{"country":"Australia"}"#);
+        let sink = Sink::default();
+
+        generate_empty_config()
+            .include_country()
+            .include_city()
+            .connect_to(address)
+            .set_log_sink(sink.clone())
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        server.join().unwrap();
+
+        let log = String::from_utf8(sink.0.lock().unwrap().clone()).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(log.trim_end()).unwrap();
+
+        assert_eq!(log.lines().count(), 1);
+        assert_eq!(entry["target"], "1.1.1.1");
+        assert_eq!(entry["fields"], 2);
+        assert_eq!(entry["status"], 200);
+        assert_eq!(entry["outcome"], "ok");
+        assert!(entry["latency_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn make_request_cached() {
+        let (address, server) = serve_once(r#"{"country":"Australia"}"#);
+        let config = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .set_cache(1);
+
+        let (ip_data, status) = config.clone().make_request_cached("1.1.1.1").await.unwrap();
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+        assert_eq!(status, CacheStatus::Miss);
+        server.join().unwrap();
+
+        let (ip_data, status) = config.clone().make_request_cached("1.1.1.1").await.unwrap();
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+        assert_eq!(status, CacheStatus::Hit);
+
+        let mut cache = ResponseCache::new(1);
+        cache.insert("a".into(), ip_data.clone());
+        cache.insert("b".into(), ip_data);
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+    }
+
+    #[tokio::test]
+    async fn fallback_base_url() {
+        let (primary, primary_server) = serve_once_with_status("503 Service Unavailable", "");
+        let (mirror, mirror_server) = serve_once(r#"{"country":"Australia"}"#);
+
+        let ip_data = generate_empty_config()
+            .include_country()
+            .connect_to(primary)
+            .set_fallback_base_url(&format!("http://{mirror}/"))
+            .unwrap()
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        let primary_request = primary_server.join().unwrap();
+        let mirror_request = mirror_server.join().unwrap();
+
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+        assert!(primary_request.starts_with("GET /json/1.1.1.1?fields="));
+        assert!(mirror_request.starts_with("GET /json/1.1.1.1?fields="));
+        assert!(mirror_request
+            .to_ascii_lowercase()
+            .contains(&format!("host: {mirror}\r\n")));
+
+        // A failed fallback would be `ConnectionFailed`
+        let result = generate_empty_config()
+            .connect_to("127.0.0.1:9".parse().unwrap())
+            .bind_to("192.0.2.1".parse().unwrap())
+            .set_fallback_base_url("http://127.0.0.1:9/")
+            .unwrap()
+            .make_request("1.1.1.1")
+            .await;
+        assert!(matches!(result, Err(IpApiError::BindFailed)));
+
+        let (primary, primary_server) = serve_once_with_status("403 Forbidden", "");
+        let result = generate_empty_config()
+            .connect_to(primary)
+            .set_fallback_base_url("http://127.0.0.1:9/")
+            .unwrap()
+            .make_request("1.1.1.1")
+            .await;
+        primary_server.join().unwrap();
+        assert!(matches!(result, Err(IpApiError::UnexpectedError(_))));
+
+        let (mirror, mirror_server) = serve_once(r#"{"country":"Australia"}"#);
+        let ip_data = generate_empty_config()
+            .include_country()
+            .connect_to("127.0.0.1:9".parse().unwrap())
+            .set_fallback_base_url(&format!("http://{mirror}/"))
+            .unwrap()
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        mirror_server.join().unwrap();
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+
+        for base_url in [
+            "https://ip-api.example.com",
+            "ip-api.example.com",
+            "http://a/b",
+        ] {
+            assert!(matches!(
+                generate_empty_config().set_fallback_base_url(base_url),
+                Err(ConfigError::UnsupportedFallbackUrl(url)) if url == base_url
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn strict() {
+        let (address, server) = serve_once(r#"{"country":"Australia","zip":""}"#);
+
+        let result = generate_empty_config()
+            .include_country()
+            .include_city()
+            .include_zip()
+            .strict()
+            .connect_to(address)
+            .make_request("1.1.1.1")
+            .await;
+        server.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(IpApiError::MissingFields(fields)) if fields == [IpDataField::City]
+        ));
+
+        let (address, server) = serve_once(r#"{"country":"Australia"}"#);
+
+        let ip_data = generate_empty_config()
+            .include_country()
+            .strict()
+            .connect_to(address)
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+    }
+
+    #[tokio::test]
+    async fn connected_or_none() {
+        let result = generate_empty_config()
+            .connect_to("127.0.0.1:9".parse().unwrap())
+            .make_request("")
+            .await;
+        assert!(matches!(result, Err(IpApiError::ConnectionFailed)));
+        assert!(matches!(super::connected_or_none(result), Ok(None)));
+        assert!(matches!(
+            super::connected_or_none(Err(IpApiError::Ipv6Unavailable)),
+            Ok(None)
+        ));
+
+        let (address, server) = serve_once("not json");
+        let result = generate_empty_config()
+            .connect_to(address)
+            .make_request("")
+            .await;
+        server.join().unwrap();
+        assert!(matches!(
+            super::connected_or_none(result),
+            Err(IpApiError::UnexpectedError(Some(message)))
+                if message == "Failed to parse body from the response"
+        ));
+    }
+
+    #[tokio::test]
+    async fn bind_to() {
+        let (address, server) = serve_once(r#"{"country":"Australia"}"#);
+
+        let ip_data = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .bind_to("127.0.0.1".parse().unwrap())
+            .make_request("1.1.1.1")
+            .await
+            .unwrap();
+        server.join().unwrap();
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+
+        let error = generate_empty_config()
+            .connect_to("127.0.0.1:9".parse().unwrap())
+            .bind_to("192.0.2.1".parse().unwrap())
+            .make_request("1.1.1.1")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IpApiError::BindFailed));
+    }
+
+    #[test]
+    fn sort_by_country() {
+        let place = |query: &str, country: Option<&str>| IpData {
+            country: country.map(str::to_string),
+            country_code: None,
+            query: Some(query.to_string()),
+            ..ip_data()
+        };
+        let mut data = vec![
+            place("unknown", None),
+            place("tokyo", Some("Japan")),
+            place("brisbane", Some("Australia")),
+            place("missing", None),
+            place("berlin", Some("Germany")),
+            place("sydney", Some("Australia")),
+        ];
+
+        crate::sort_by_country(&mut data);
+
+        let queries: Vec<_> = data
+            .iter()
+            .filter_map(|ip_data| ip_data.query.as_deref())
+            .collect();
+        // The sort is stable, so equal and missing countries keep their order
+        assert_eq!(
+            queries,
+            ["brisbane", "sydney", "berlin", "tokyo", "unknown", "missing"]
+        );
+    }
+
+    #[test]
+    fn sort_by_distance_from() {
+        let place = |query: &str, lat: Option<f32>, lon: Option<f32>| IpData {
+            lat,
+            lon,
+            query: Some(query.to_string()),
+            ..ip_data()
+        };
+        let mut data = vec![
+            place("unknown", None, None),
+            place("sydney", Some(-33.8688), Some(151.2093)),
+            place("brisbane", Some(-27.4766), Some(153.0166)),
+            place("tokyo", Some(35.6762), Some(139.6503)),
+        ];
+
+        crate::sort_by_distance_from(
+            &mut data,
+            Coordinates {
+                lat: -28.0167,
+                lon: 153.4,
+            },
+        );
+
+        let queries: Vec<_> = data
+            .iter()
+            .filter_map(|ip_data| ip_data.query.as_deref())
+            .collect();
+        assert_eq!(queries, ["brisbane", "sydney", "tokyo", "unknown"]);
+
+        let distance = data[0]
+            .coordinates()
+            .unwrap()
+            .distance_km(&data[1].coordinates().unwrap());
+        assert!((distance - 732.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn batch_invalid_policy() {
+        let targets = vec!["1.1.1.1", "192.168.1.1", "127.0.0.1", "fd00::1", "8.8.8.8"];
+
+        assert!(matches!(
+            generate_empty_config()
+                .set_batch_invalid_policy(BatchInvalidPolicy::Error)
+                .apply_batch_invalid_policy(targets.clone()),
+            Err(IpApiError::PrivateRange)
+        ));
+        assert_eq!(
+            generate_empty_config()
+                .set_batch_invalid_policy(BatchInvalidPolicy::Skip)
+                .apply_batch_invalid_policy(targets.clone())
+                .unwrap(),
+            ["1.1.1.1", "8.8.8.8"]
+        );
+        assert_eq!(
+            generate_empty_config()
+                .apply_batch_invalid_policy(targets.clone())
+                .unwrap(),
+            targets
+        );
+
+        // The same targets are removed as reported by `is_valid_query`
+        let remaining: Vec<&str> = targets
+            .iter()
+            .copied()
+            .filter(|target| {
+                !matches!(
+                    super::is_valid_query(target),
+                    QueryKind::Private | QueryKind::Reserved
+                )
+            })
+            .collect();
+        assert_eq!(remaining, ["1.1.1.1", "8.8.8.8"]);
+    }
+
+    #[tokio::test]
+    async fn batch_invalid_policy_localized_and_mixed() {
+        let result = generate_empty_config()
+            .set_batch_invalid_policy(BatchInvalidPolicy::Error)
+            .make_batch_request_localized(vec![
+                ("1.1.1.1", IpApiLanguage::De),
+                ("127.0.0.1", IpApiLanguage::Fr),
+            ])
+            .await;
+        assert!(matches!(result, Err(IpApiError::ReservedRange)));
+
+        // Nothing is left, so nothing is sent
+        let result = generate_empty_config()
+            .set_batch_invalid_policy(BatchInvalidPolicy::Skip)
+            .make_batch_request_mixed(vec![
+                ("192.168.1.1", generate_empty_config().include_country()),
+                ("fd00::1", generate_empty_config().include_city()),
+            ])
+            .await;
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    fn asn() {
+        let ip_data = ip_data();
+        assert_eq!(ip_data.asn(), Some(13335));
+        assert_eq!(ip_data.as_org(), Some("Cloudflare, Inc."));
+
+        let ip_data = IpData {
+            as_field: Some("AS13335".to_string()),
+            ..ip_data
+        };
+        assert_eq!(ip_data.asn(), Some(13335));
+        assert_eq!(ip_data.as_org(), None);
+
+        let ip_data = IpData {
+            as_field: Some("".to_string()),
+            ..ip_data
+        };
+        assert_eq!(ip_data.asn(), None);
+    }
+
+    #[test]
+    fn validate_fields() {
+        assert_eq!(
+            generate_empty_config()
+                .set_fields_mask(32768 | 1)
+                .validate_fields(),
+            Ok(())
+        );
+        assert_eq!(
+            generate_empty_config().set_fields_mask(1).validate_fields(),
+            Err(ConfigError::MissingMessageField)
+        );
+        assert_eq!(
+            generate_empty_config()
+                .set_fields_mask(32768 | 16384 | 1)
+                .validate_fields(),
+            Ok(())
+        );
+        assert_eq!(
+            generate_empty_config()
+                .set_fields_mask(32768 | 1 << 30)
+                .validate_fields(),
+            Err(ConfigError::UnrecognizedFieldBits(1 << 30))
+        );
+
+        let config = generate_empty_config()
+            .set_fields_mask(32768 | 1)
+            .include_country()
+            .include_city();
+        assert_eq!(config.fields_query_value(), (32768 | 1 | 16).to_string());
+    }
+
+    #[tokio::test]
+    async fn validate() {
+        assert_eq!(generate_empty_config().validate(), Ok(()));
+        assert_eq!(
+            generate_empty_config()
+                .include_country()
+                .strict()
+                .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            generate_empty_config().strict().validate(),
+            Err(ConfigError::StrictWithoutFields)
+        );
+        assert_eq!(
+            generate_empty_config()
+                .set_fields_mask(1)
+                .strict()
+                .validate(),
+            Err(ConfigError::MissingMessageField)
+        );
+        assert_eq!(
+            generate_empty_config()
+                .set_fields_mask(32768 | 1 << 30)
+                .validate(),
+            Err(ConfigError::UnrecognizedFieldBits(1 << 30))
+        );
+
+        // Checked before anything is sent
+        let result = generate_empty_config()
+            .strict()
+            .connect_to("127.0.0.1:9".parse().unwrap())
+            .make_request("1.1.1.1")
+            .await;
+        assert!(matches!(
+            result,
+            Err(IpApiError::InvalidConfig(ConfigError::StrictWithoutFields))
+        ));
+    }
+
+    #[cfg(feature = "stream")]
+    fn batch_stream(targets: &[&str], body: &str) -> crate::BatchReader {
+        let mut stream = crate::BatchReader::new(generate_empty_config(), Default::default());
+        stream.targets = targets.iter().map(|target| target.to_string()).collect();
+        stream.buffer.extend_from_slice(body.as_bytes());
+
+        stream
+    }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn batch_stream_take_result() {
+        let mut stream = batch_stream(&["1.1.1.1", "1.1.1.256", "8.8.8.8"], "");
+        let chunks = [
+            r#"[{"country":"Austr"#,
+            r#"alia","city":"a \"}\" b"},"#,
+            r#" {"message":"invalid query"}, 4"#,
+            r#"2 ]"#,
+        ];
+        let mut results = Vec::new();
+
+        for chunk in chunks {
+            stream.buffer.extend_from_slice(chunk.as_bytes());
+
+            while let Some(result) = stream.take_result() {
+                results.push(result);
+            }
+        }
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0].as_ref().unwrap().city.as_deref(),
+            Some("a \"}\" b")
+        );
+        assert!(matches!(results[1], Err(IpApiError::InvalidQuery)));
+        assert!(matches!(results[2], Err(IpApiError::UnexpectedError(_))));
+        assert!(stream.finish_response().is_ok());
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn make_batch_stream() {
+        let (address, server) =
+            serve_once(r#"[{"country":"Australia"}, {"message":"invalid query"}]"#);
+        let mut stream = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .make_batch_stream(vec!["1.1.1.1", "1.1.1.256"])
+            .unwrap();
+
+        let ip_data = stream.next().await.unwrap().unwrap();
+        assert_eq!(ip_data.country.as_deref(), Some("Australia"));
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(IpApiError::InvalidQuery))
+        ));
+        assert!(stream.next().await.is_none());
+        assert!(server.join().unwrap().starts_with("POST /batch"));
+
+        // The limit applies to the whole body, not to a single result
+        let (address, server) = serve_once(r#"[{"country":"Australia"},{"country":"Australia"}]"#);
+        let mut stream = generate_empty_config()
+            .include_country()
+            .connect_to(address)
+            .set_max_response_bytes(30)
+            .make_batch_stream(vec!["1.1.1.1", "8.8.8.8"])
+            .unwrap();
+
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(IpApiError::ResponseTooLarge))
+        ));
+        assert!(stream.next().await.is_none());
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn batch_stream_not_array() {
+        let mut stream = batch_stream(
+            &["1.1.1.1"],
+            r#"{"status":"fail","message":"too many requests"}"#,
+        );
+
+        assert!(stream.take_result().is_none());
+        assert!(matches!(
+            stream.finish_response(),
+            Err(IpApiError::UnexpectedError(Some(message))) if message == "too many requests"
+        ));
+
+        let mut stream = batch_stream(&["1.1.1.1"], r#""unexpected""#);
+        assert!(stream.take_result().is_none());
+        assert!(stream.finish_response().is_err());
+    }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn batch_stream_count_mismatch() {
+        let mut stream = batch_stream(&["1.1.1.1", "8.8.8.8"], r#"[{"country":"Australia"}]"#);
+        assert!(stream.take_result().unwrap().is_ok());
+        assert!(stream.take_result().is_none());
+        assert!(matches!(
+            stream.finish_response(),
+            Err(IpApiError::UnexpectedError(Some(message)))
+                if message == "Received fewer results than targets"
+        ));
+
+        let mut stream = batch_stream(&["1.1.1.1"], r#"[{"country":"Australia"},{}]"#);
+        stream
+            .requests
+            .push_back((String::new(), vec!["8.8.8.8".into()]));
+        assert!(stream.take_result().unwrap().is_ok());
+        assert!(matches!(
+            stream.take_result(),
+            Some(Err(IpApiError::UnexpectedError(Some(message))))
+                if message == "Received more results than targets"
+        ));
+        assert!(stream.requests.is_empty());
+    }
+
+    #[test]
+    fn is_geographically_consistent() {
+        assert_eq!(ip_data().is_geographically_consistent(), Some(true));
+        assert_eq!(
+            IpData {
+                continent_code: Some("EU".to_string()),
+                ..ip_data()
+            }
+            .is_geographically_consistent(),
+            Some(false)
+        );
+        assert_eq!(
+            IpData {
+                country_code: Some("ZZ".to_string()),
+                ..ip_data()
+            }
+            .is_geographically_consistent(),
+            None
+        );
+    }
+
+    #[test]
+    fn deserialize_flexible_bool() {
+        let ip_data: IpData =
+            serde_json::from_str(r#"{"mobile":true,"proxy":false,"hosting":null}"#).unwrap();
+        assert_eq!(
+            (ip_data.mobile, ip_data.proxy, ip_data.hosting),
+            (Some(true), Some(false), None)
+        );
+
+        let ip_data: IpData =
+            serde_json::from_str(r#"{"mobile":"true","proxy":"0","hosting":"1"}"#).unwrap();
+        assert_eq!(
+            (ip_data.mobile, ip_data.proxy, ip_data.hosting),
+            (Some(true), Some(false), Some(true))
+        );
+
+        let ip_data: IpData = serde_json::from_str(r#"{"mobile":"false"}"#).unwrap();
+        assert_eq!(
+            (ip_data.mobile, ip_data.proxy, ip_data.hosting),
+            (Some(false), None, None)
+        );
+
+        assert!(serde_json::from_str::<IpData>(r#"{"mobile":"yes"}"#).is_err());
+    }
+
+    #[test]
+    fn deserialize_flexible_float() {
+        let number: IpData = serde_json::from_str(r#"{"lat":-27.47,"lon":153.02}"#).unwrap();
+        let string: IpData = serde_json::from_str(r#"{"lat":"-27.47","lon":" 153.02 "}"#).unwrap();
+        assert_eq!((number.lat, number.lon), (Some(-27.47), Some(153.02)));
+        assert_eq!((string.lat, string.lon), (number.lat, number.lon));
+
+        let ip_data: IpData = serde_json::from_str(r#"{"lat":null}"#).unwrap();
+        assert_eq!((ip_data.lat, ip_data.lon), (None, None));
+
+        assert!(serde_json::from_str::<IpData>(r#"{"lat":"north"}"#).is_err());
+        assert!(serde_json::from_str::<IpData>(r#"{"lon":"NaN"}"#).is_err());
+    }
+
+    #[test]
+    fn network_category() {
+        assert_eq!(ip_data().network_category(), NetworkCategory::Cloud);
+
+        let ip_data = IpData {
+            isp: Some("Massachusetts Institute of Technology".to_string()),
+            org: Some("MIT".to_string()),
+            asname: Some("MIT-GATEWAYS".to_string()),
+            hosting: Some(false),
+            ..ip_data()
+        };
+        assert_eq!(ip_data.network_category(), NetworkCategory::Education);
+
+        let ip_data = IpData {
+            isp: Some("MIT".to_string()),
+            ..ip_data
+        };
+        assert_eq!(ip_data.network_category(), NetworkCategory::Unknown);
+
+        let ip_data = IpData {
+            org: Some("mit.edu".to_string()),
+            ..ip_data
+        };
+        assert_eq!(ip_data.network_category(), NetworkCategory::Education);
+
+        let ip_data = IpData {
+            isp: Some("Deutsche Telekom AG".to_string()),
+            org: None,
+            asname: None,
+            ..ip_data
+        };
+        assert_eq!(ip_data.network_category(), NetworkCategory::Isp);
+    }
+
+    #[test]
+    fn coarse_location() {
+        let ip_data = ip_data();
+        let coarse_location = |precision| {
+            let coordinates = ip_data.coarse_location(precision).unwrap();
+            (coordinates.lat, coordinates.lon)
+        };
+
+        assert_eq!(coarse_location(CoarsePrecision::Country), (-27.0, 153.0));
+        assert_eq!(coarse_location(CoarsePrecision::Region), (-27.5, 153.0));
+        assert_eq!(coarse_location(CoarsePrecision::City), (-27.5, 153.0));
+        assert!(IpData {
+            lat: None,
+            ..ip_data.clone()
+        }
+        .coarse_location(CoarsePrecision::City)
+        .is_none());
+    }
+
+    #[test]
+    fn to_geojson_collection() {
+        let data = [
+            ip_data(),
+            IpData {
+                lat: None,
+                ..ip_data()
+            },
+        ];
+
+        assert_eq!(
+            crate::to_geojson_collection(&data),
+            json!({
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [153.0166_f32, -27.4766_f32],
+                    },
+                    "properties": {
+                        "continent": "Oceania",
+                        "continentCode": "OC",
+                        "country": "Australia",
+                        "countryCode": "AU",
+                        "region": "QLD",
+                        "regionName": "Queensland",
+                        "city": "South Brisbane",
+                        "district": "",
+                        "zip": "4101",
+                        "timezone": "Australia/Brisbane",
+                        "offset": 36000,
+                        "currency": "AUD",
+                        "isp": "Cloudflare, Inc",
+                        "org": "APNIC and Cloudflare DNS Resolver project",
+                        "as": "AS13335 Cloudflare, Inc.",
+                        "asname": "CLOUDFLARENET",
+                        "reverse": "one.one.one.one",
+                        "mobile": false,
+                        "proxy": false,
+                        "hosting": true,
+                        "query": "1.1.1.1",
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn backoff() {
+        let backoff = Backoff::ExponentialJitter {
+            base: Duration::from_millis(100),
+            max: Duration::from_millis(300),
+        };
+
+        for _ in 0..100 {
+            assert!(backoff.retry_wait(1) <= Duration::from_millis(100));
+            assert!(backoff.retry_wait(2) <= Duration::from_millis(200));
+            assert!(backoff.retry_wait(40) <= Duration::from_millis(300));
+            assert!(backoff.rate_limit_jitter() <= Duration::from_millis(100));
+        }
+
+        assert_eq!(Backoff::Immediate.retry_wait(3), Duration::ZERO);
+        assert_eq!(Backoff::Immediate.rate_limit_jitter(), Duration::ZERO);
+
+        let fixed = Backoff::Fixed(Duration::from_millis(50));
+        assert_eq!(fixed.retry_wait(1), Duration::from_millis(50));
+        assert_eq!(fixed.retry_wait(3), Duration::from_millis(50));
+        assert_eq!(fixed.rate_limit_jitter(), Duration::ZERO);
+        assert!(Backoff::default().retry_wait(1) > Duration::ZERO);
+    }
+
+    #[cfg(all(feature = "isocountry", feature = "chrono-tz"))]
+    #[test]
+    fn timezone_country() {
+        assert_eq!(
+            ip_data().timezone_country(),
+            Some(isocountry::CountryCode::AUS)
+        );
+        assert_eq!(
+            IpData {
+                timezone: Some("UTC".to_string()),
+                ..ip_data()
+            }
+            .timezone_country(),
+            None
+        );
+        assert_eq!(
+            IpData {
+                timezone: Some("Europe/Kiev".to_string()),
+                ..ip_data()
+            }
+            .timezone_country(),
+            Some(isocountry::CountryCode::UKR)
+        );
+        assert_eq!(
+            IpData {
+                timezone: Some("Asia/Calcutta".to_string()),
+                ..ip_data()
+            }
+            .timezone_country(),
+            Some(isocountry::CountryCode::IND)
+        );
+
+        // Fails when chrono-tz gets zones that the snapshots don't cover
+        let unbound = [
+            "Africa/Timbuktu",
+            "America/Coral_Harbour",
+            "Antarctica/South_Pole",
+            "Atlantic/Jan_Mayen",
+            "Pacific/Johnston",
+            "Pacific/Yap",
+        ];
+        for timezone in chrono_tz::TZ_VARIANTS {
+            let name = timezone.name();
+
+            if !name.contains('/') || name.starts_with("Etc/") || unbound.contains(&name) {
+                continue;
+            }
+
+            let ip_data = IpData {
+                timezone: Some(name.to_string()),
+                ..ip_data()
+            };
+            assert!(ip_data.timezone_country().is_some(), "{name}");
+        }
+    }
+
+    #[test]
+    fn diff() {
+        let ip_data = ip_data();
+        assert_eq!(ip_data.diff(&ip_data), vec![]);
+
+        let other = IpData {
+            isp: Some("Example ISP".to_string()),
+            proxy: Some(true),
+            // Added and removed fields are skipped
+            district: None,
+            ..ip_data.clone()
+        };
+        let ip_data = IpData {
+            mobile: None,
+            ..ip_data
+        };
+        assert_eq!(
+            ip_data.diff(&other),
+            vec![
+                FieldDiff {
+                    field: IpDataField::Isp,
+                    old: Some("Cloudflare, Inc".to_string()),
+                    new: Some("Example ISP".to_string()),
+                },
+                FieldDiff {
+                    field: IpDataField::Proxy,
+                    old: Some("false".to_string()),
+                    new: Some("true".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            other.diff(&ip_data),
+            vec![
+                FieldDiff {
+                    field: IpDataField::Isp,
+                    old: Some("Example ISP".to_string()),
+                    new: Some("Cloudflare, Inc".to_string()),
+                },
+                FieldDiff {
+                    field: IpDataField::Proxy,
+                    old: Some("true".to_string()),
+                    new: Some("false".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn to_sorted_map() {
+        let ip_data: IpData = serde_json::from_value(json!({
+            "offset": 36000,
+            "hosting": true,
+            "countryCode": "AU",
+            "city": "South Brisbane",
+            "as": "AS13335 Cloudflare, Inc.",
+        }))
+        .unwrap();
+        let map = ip_data.to_sorted_map();
+
+        assert_eq!(
+            map.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["as", "city", "countryCode", "hosting", "offset"]
+        );
+        assert_eq!(map["as"], json!("AS13335 Cloudflare, Inc."));
+        assert_eq!(map["hosting"], json!(true));
+        assert_eq!(map["offset"], json!(36000));
+    }
+
+    #[test]
+    fn difference() {
+        let location = generate_empty_config().include_country().include_city();
+        let network = generate_empty_config().include_isp().include_as_field();
+        let both = generate_empty_config().include_country().include_isp();
+
+        assert_eq!(
+            location.difference(&network),
+            [IpDataField::Country, IpDataField::City]
+        );
+        assert_eq!(
+            network.difference(&location),
+            [IpDataField::Isp, IpDataField::AsField]
+        );
+        assert_eq!(location.difference(&both), [IpDataField::City]);
+        assert_eq!(both.difference(&location), [IpDataField::Isp]);
+        assert!(location.difference(&location).is_empty());
+    }
+
+    #[test]
+    fn completeness() {
+        let ip_data = ip_data();
+
+        assert_eq!(ip_data.completeness(&generate_empty_config()), 1.0);
+        assert_eq!(
+            ip_data.completeness(&generate_empty_config().include_city().include_district()),
+            0.5
+        );
+        assert_eq!(
+            ip_data.completeness(&generate_maximum_config()),
+            21.0 / 22.0
+        );
+    }
+
+    #[test]
+    fn to_csv() {
+        let mut ip_data = ip_data();
+        ip_data.continent = None;
+
+        let csv = crate::to_csv(&[ip_data]);
+        let mut lines = csv.lines();
+
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("continent,continentCode,country,"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(",OC,Australia,AU,QLD,Queensland,South Brisbane,,4101,-27.4766,"));
+        assert!(csv.contains(r#","Cloudflare, Inc",APNIC and"#));
+    }
+
+    #[test]
+    fn dry_run() {
+        let request = generate_empty_config()
+            .include_country()
+            .set_language(IpApiLanguage::Ru)
+            .dry_run("1.1.1.1");
+
+        assert_eq!(request.method, hyper::Method::GET);
+        assert_eq!(
+            request.url,
+            "http://ip-api.com/json/1.1.1.1?fields=32769&lang=ru"
+        );
+        assert!(request.body.is_none());
+    }
+
+    #[test]
+    fn normalize_target() {
+        assert_eq!(
+            generate_empty_config().dry_run("Example.COM.").url,
+            generate_empty_config().dry_run("example.com").url
+        );
+        assert_eq!(
+            generate_empty_config().dry_run("2606:4700:4700::1111").url,
+            "http://ip-api.com/json/2606:4700:4700::1111?fields=32768"
+        );
+        assert_eq!(IpApiConfig::normalize_target("FE80::1"), "FE80::1");
+    }
+
+    #[test]
+    fn is_valid_query() {
+        assert_eq!(super::is_valid_query("1.1.1.1"), QueryKind::Ipv4);
+        assert_eq!(
+            super::is_valid_query("2606:4700:4700::1111"),
+            QueryKind::Ipv6
+        );
+        assert_eq!(super::is_valid_query("example.com"), QueryKind::Domain);
+        assert_eq!(super::is_valid_query("Example.COM."), QueryKind::Domain);
+        assert_eq!(super::is_valid_query("192.168.1.1"), QueryKind::Private);
+        assert_eq!(super::is_valid_query("fd00::1"), QueryKind::Private);
+        assert_eq!(super::is_valid_query("127.0.0.1"), QueryKind::Reserved);
+        assert_eq!(super::is_valid_query("2001:db8::1"), QueryKind::Reserved);
+        assert_eq!(super::is_valid_query("1.1.1.one"), QueryKind::Invalid);
+        assert_eq!(super::is_valid_query("1.1.1.256"), QueryKind::Invalid);
+        assert_eq!(super::is_valid_query("example"), QueryKind::Invalid);
+        assert_eq!(super::is_valid_query("-example.com"), QueryKind::Invalid);
+        assert_eq!(super::is_valid_query("exa mple.com"), QueryKind::Invalid);
+        assert_eq!(super::is_valid_query(""), QueryKind::Invalid);
+    }
+}
+
+/// Represents all the ways that a request can fail
+#[derive(Clone, Debug)]
+pub enum IpApiError {
+    /// The request was cancelled via a [`CancellationToken`]
+    Cancelled,
+
+    /// A batch request split into several requests (more than 100 targets) failed
+    /// for a reason other than the rate limit (see [`IpApiError::RateLimitPartial`])
+    ///
+    /// Returned only by [`IpApiConfig::make_batch_request`] and its variants
+    /// that split the targets automatically
+    BatchPartial {
+        /// Results of the targets requested before the failure,
+        /// in the same order as the targets
+        completed: Vec<IpData>,
+
+        /// The error of the failed request
+        error: Box<IpApiError>,
+    },
+
+    /// Failed to bind to [the local address](IpApiConfig::bind_to),
+    /// usually because it doesn't belong to this host
+    BindFailed,
+
+    /// Failed to establish a connection to the server
+    /// (e.g. it's unreachable, refused the connection or its name couldn't be resolved)
+    ///
+    /// Usually transient, so the request can be retried
+    ConnectionFailed,
+
+    /// The connection broke before the whole response was received
+    ///
+    /// Usually transient, so the request can be retried
+    ConnectionLost,
+
+    /// Non-existent domain
+    ///
+    /// Returned only if the target looks like a domain (see [`QueryKind::Domain`]).
+    /// Contains the domain that could not be resolved, as passed
+    ///
+    /// # Example
+    ///
+    /// test.google.com
+    DomainNotResolved(String),
+
+    /// The response was successful but its body was empty
+    ///
+    /// Usually a transient server or intermediary quirk, so the request can be retried
+    EmptyResponse,
+
+    /// The config is incoherent, see [`IpApiConfig::validate`]
+    InvalidConfig(ConfigError),
+
+    /// The server has no IPv6 address, so it can't be connected to
+    /// [only over IPv6](IpApiConfig::prefer_ipv6)
+    Ipv6Unavailable,
+
+    /// Incorrect IP address (or a target that doesn't look like a domain)
+    ///
+    /// # Example
+    ///
+    /// 1.1.1.256 **OR** 1.1.1.one
+    InvalidQuery,
+
+    /// Some of the requested fields are missing in the response
+    ///
+    /// Returned only in [strict mode](IpApiConfig::strict)
+    MissingFields(Vec<IpDataField>),
+
+    /// IPs in your network
+    ///
+    /// # Example
+    ///
+    /// 192.168.1.1
+    PrivateRange,
+
+    /// [The request quota](IpApiConfig::set_request_quota) is used up,
+    /// so the request wasn't sent
+    QuotaExceeded,
+
+    /// [ip-api.com API](https://ip-api.com/docs/api:json) is limited to 45 requests per minute
+    /// from one IP address
+    ///
+    /// Contains the remaining time before a possible re-request in seconds
+    RateLimit(u8),
+
+    /// Rate limit was reached by a batch request split into several requests
+    /// (more than 100 targets)
+    ///
+    /// Returned instead of [`IpApiError::RateLimit`], even if the first request is rejected.
+    /// Returned only by [`IpApiConfig::make_batch_request`] and its variants
+    /// that split the targets automatically
+    RateLimitPartial {
+        /// Results of the targets requested before reaching the rate limit,
+        /// in the same order as the targets
+        completed: Vec<IpData>,
+
+        /// Remaining time before a possible re-request
+        retry_in: Duration,
+    },
+
+    /// Reserved Range
+    ///
+    /// # Example
+    ///
+    /// 127.0.0.1 **OR** localhost
+    ReservedRange,
+
+    /// The body of the response is bigger than
+    /// [the maximum size](IpApiConfig::set_max_response_bytes)
+    ResponseTooLarge,
+
+    /// Downloading the body of the response took longer than
+    /// [the read timeout](IpApiConfig::set_read_timeout)
+    Timeout,
+
+    /// Unexpected Error
+    ///
+    /// May contain additional information
+    UnexpectedError(Option<String>),
+}
+
+/// Represents all the ways that a config can be invalid
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// [`IpDataField::Message`] is not included, so errors can't be detected
+    ///
+    /// Possible only after [`IpApiConfig::set_fields_mask`]
+    MissingMessageField,
+
+    /// [Strict mode](IpApiConfig::strict) is enabled, but no fields are included,
+    /// so there is nothing to check
+    StrictWithoutFields,
+
+    /// Some field names are not recognized
+    ///
+    /// Contains all unrecognized names
+    UnknownFieldNames(Vec<String>),
+
+    /// The fallback base URL isn't an absolute `http` URL without a path
+    ///
+    /// Contains the rejected URL.
+    /// Possible only after [`IpApiConfig::set_fallback_base_url`]
+    UnsupportedFallbackUrl(String),
+
+    /// The fields mask contains bits that don't correspond to any [`IpDataField`]
+    ///
+    /// Contains the unrecognized bits.
+    /// Possible only after [`IpApiConfig::set_fields_mask`]
+    UnrecognizedFieldBits(u32),
+}
+
+impl IpApiError {
+    /// Whether sending the request again may succeed
+    fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            IpApiError::ConnectionFailed
+                | IpApiError::ConnectionLost
+                | IpApiError::EmptyResponse
+                | IpApiError::Timeout
+        )
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl IpApiError {
+    fn kind(&self) -> &'static str {
+        match self {
+            IpApiError::BatchPartial { .. } => "batch_partial",
+            IpApiError::BindFailed => "bind_failed",
+            IpApiError::Cancelled => "cancelled",
+            IpApiError::ConnectionFailed => "connection_failed",
+            IpApiError::ConnectionLost => "connection_lost",
+            IpApiError::DomainNotResolved(_) => "domain_not_resolved",
+            IpApiError::EmptyResponse => "empty_response",
+            IpApiError::InvalidConfig(_) => "invalid_config",
+            IpApiError::InvalidQuery => "invalid_query",
+            IpApiError::Ipv6Unavailable => "ipv6_unavailable",
+            IpApiError::MissingFields(_) => "missing_fields",
+            IpApiError::PrivateRange => "private_range",
+            IpApiError::QuotaExceeded => "quota_exceeded",
+            IpApiError::RateLimit(_) => "rate_limit",
+            IpApiError::RateLimitPartial { .. } => "rate_limit_partial",
+            IpApiError::ReservedRange => "reserved_range",
+            IpApiError::ResponseTooLarge => "response_too_large",
+            IpApiError::Timeout => "timeout",
+            IpApiError::UnexpectedError(_) => "unexpected_error",
+        }
+    }
+}
+
+/// Represents all available languages for [`IpData`]
+#[derive(Clone, Debug)]
+pub enum IpApiLanguage {
+    /// Deutsch (German)
+    De,
+
+    /// English (default)
+    En,
+
+    /// Español (Spanish)
+    Es,
+
+    /// Français (French)
+    Fr,
+
+    /// 日本語 (Japanese)
+    Ja,
+
+    /// Português - Brasil (Portuguese - Brasil)
+    PtBr,
+
+    /// Русский (Russian)
+    Ru,
+
+    /// 中国 (Chinese)
+    ZhCn,
+}
+
+impl IpApiLanguage {
+    fn code(&self) -> &'static str {
+        match self {
+            IpApiLanguage::De => "de",
+            IpApiLanguage::En => "en",
+            IpApiLanguage::Es => "es",
+            IpApiLanguage::Fr => "fr",
+            IpApiLanguage::Ja => "ja",
+            IpApiLanguage::PtBr => "pt-BR",
+            IpApiLanguage::Ru => "ru",
+            IpApiLanguage::ZhCn => "zh-CN",
+        }
+    }
+}
+
+/// Represents all fields that can be requested
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IpDataField {
+    /// [`continent`](struct.IpData.html#structfield.continent)
+    Continent,
+
+    /// [`continent_code`](struct.IpData.html#structfield.continent_code)
+    ContinentCode,
+
+    /// [`country`](struct.IpData.html#structfield.country)
+    Country,
+
+    /// [`country_code`](struct.IpData.html#structfield.country_code)
+    CountryCode,
+
+    /// [`region`](struct.IpData.html#structfield.region)
+    Region,
+
+    /// [`region_name`](struct.IpData.html#structfield.region_name)
+    RegionName,
+
+    /// [`city`](struct.IpData.html#structfield.city)
+    City,
+
+    /// [`district`](struct.IpData.html#structfield.district)
+    District,
+
+    /// [`zip`](struct.IpData.html#structfield.zip)
+    Zip,
+
+    /// [`lat`](struct.IpData.html#structfield.lat)
+    Lat,
+
+    /// [`lon`](struct.IpData.html#structfield.lon)
+    Lon,
+
+    /// [`timezone`](struct.IpData.html#structfield.timezone)
+    Timezone,
+
+    /// [`offset`](struct.IpData.html#structfield.offset)
+    Offset,
+
+    /// [`currency`](struct.IpData.html#structfield.currency)
+    Currency,
+
+    /// [`isp`](struct.IpData.html#structfield.isp)
+    Isp,
+
+    /// [`org`](struct.IpData.html#structfield.org)
+    Org,
+
+    /// [`as_field`](struct.IpData.html#structfield.as_field)
+    AsField,
+
+    /// [`asname`](struct.IpData.html#structfield.asname)
+    Asname,
+
+    /// [`reverse`](struct.IpData.html#structfield.reverse)
+    Reverse,
+
+    /// [`mobile`](struct.IpData.html#structfield.mobile)
+    Mobile,
+
+    /// [`proxy`](struct.IpData.html#structfield.proxy)
+    Proxy,
+
+    /// [`hosting`](struct.IpData.html#structfield.hosting)
+    Hosting,
+
+    /// [`query`](struct.IpData.html#structfield.query)
+    Query,
+
+    /// Error message of a failed request
+    ///
+    /// Always included, since it is required to detect errors
+    Message,
+}
+
+impl IpDataField {
+    const ALL: [IpDataField; 24] = [
+        IpDataField::Continent,
+        IpDataField::ContinentCode,
+        IpDataField::Country,
+        IpDataField::CountryCode,
+        IpDataField::Region,
+        IpDataField::RegionName,
+        IpDataField::City,
+        IpDataField::District,
+        IpDataField::Zip,
+        IpDataField::Lat,
+        IpDataField::Lon,
+        IpDataField::Timezone,
+        IpDataField::Offset,
+        IpDataField::Currency,
+        IpDataField::Isp,
+        IpDataField::Org,
+        IpDataField::AsField,
+        IpDataField::Asname,
+        IpDataField::Reverse,
+        IpDataField::Mobile,
+        IpDataField::Proxy,
+        IpDataField::Hosting,
+        IpDataField::Query,
+        IpDataField::Message,
+    ];
+
+    fn bit(&self) -> u32 {
+        match self {
+            IpDataField::Continent => 1048576,
+            IpDataField::ContinentCode => 2097152,
+            IpDataField::Country => 1,
+            IpDataField::CountryCode => 2,
+            IpDataField::Region => 4,
+            IpDataField::RegionName => 8,
+            IpDataField::City => 16,
+            IpDataField::District => 524288,
+            IpDataField::Zip => 32,
+            IpDataField::Lat => 64,
+            IpDataField::Lon => 128,
+            IpDataField::Timezone => 256,
+            IpDataField::Offset => 33554432,
+            IpDataField::Currency => 8388608,
+            IpDataField::Isp => 512,
+            IpDataField::Org => 1024,
+            IpDataField::AsField => 2048,
+            IpDataField::Asname => 4194304,
+            IpDataField::Reverse => 4096,
+            IpDataField::Mobile => 65536,
+            IpDataField::Proxy => 131072,
+            IpDataField::Hosting => 16777216,
+            IpDataField::Query => 8192,
+            IpDataField::Message => 32768,
+        }
+    }
+
+    fn typical_value_bytes(&self) -> usize {
+        match self {
+            IpDataField::Continent => 15,
+            IpDataField::ContinentCode => 4,
+            IpDataField::Country => 16,
+            IpDataField::CountryCode => 4,
+            IpDataField::Region => 5,
+            IpDataField::RegionName => 16,
+            IpDataField::City => 16,
+            IpDataField::District => 12,
+            IpDataField::Zip => 8,
+            IpDataField::Lat => 8,
+            IpDataField::Lon => 9,
+            IpDataField::Timezone => 21,
+            IpDataField::Offset => 6,
+            IpDataField::Currency => 5,
+            IpDataField::Isp => 26,
+            IpDataField::Org => 32,
+            IpDataField::AsField => 32,
+            IpDataField::Asname => 16,
+            IpDataField::Reverse => 26,
+            IpDataField::Mobile => 5,
+            IpDataField::Proxy => 5,
+            IpDataField::Hosting => 5,
+            IpDataField::Query => 15,
+            // Returned only if the request fails
+            IpDataField::Message => 0,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        IpDataField::ALL
+            .into_iter()
+            .find(|field| field.name() == name)
+    }
+
+    /// Get the field name used in the
+    /// [ip-api.com API](https://ip-api.com/docs/api:json#returned-data) JSON response
+    ///
+    /// # Example
+    ///
+    /// `countryCode` for [`IpDataField::CountryCode`] or `as` for [`IpDataField::AsField`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            IpDataField::Continent => "continent",
+            IpDataField::ContinentCode => "continentCode",
+            IpDataField::Country => "country",
+            IpDataField::CountryCode => "countryCode",
+            IpDataField::Region => "region",
+            IpDataField::RegionName => "regionName",
+            IpDataField::City => "city",
+            IpDataField::District => "district",
+            IpDataField::Zip => "zip",
+            IpDataField::Lat => "lat",
+            IpDataField::Lon => "lon",
+            IpDataField::Timezone => "timezone",
+            IpDataField::Offset => "offset",
+            IpDataField::Currency => "currency",
+            IpDataField::Isp => "isp",
+            IpDataField::Org => "org",
+            IpDataField::AsField => "as",
+            IpDataField::Asname => "asname",
+            IpDataField::Reverse => "reverse",
+            IpDataField::Mobile => "mobile",
+            IpDataField::Proxy => "proxy",
+            IpDataField::Hosting => "hosting",
+            IpDataField::Query => "query",
+            IpDataField::Message => "message",
+        }
+    }
+}
+
+/// Value of a single [`IpData`] field
+///
+/// Yielded by [`IpData::iter`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldValue<'a> {
+    /// Text value
+    Str(&'a str),
+
+    /// Floating point value ([`lat`](struct.IpData.html#structfield.lat)
+    /// or [`lon`](struct.IpData.html#structfield.lon))
+    F32(f32),
+
+    /// Integer value ([`offset`](struct.IpData.html#structfield.offset))
+    I32(i32),
+
+    /// Boolean value ([`mobile`](struct.IpData.html#structfield.mobile),
+    /// [`proxy`](struct.IpData.html#structfield.proxy)
+    /// or [`hosting`](struct.IpData.html#structfield.hosting))
+    Bool(bool),
+}
+
+impl Display for FieldValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Str(value) => write!(f, "{value}"),
+            FieldValue::F32(value) => write!(f, "{value}"),
+            FieldValue::I32(value) => write!(f, "{value}"),
+            FieldValue::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A field that differs between two [`IpData`]
+///
+/// Returned by [`IpData::diff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// The changed field
+    pub field: IpDataField,
+
+    /// Value of the field in the original [`IpData`]
+    pub old: Option<String>,
+
+    /// Value of the field in the new [`IpData`]
+    pub new: Option<String>,
+}
+
+/// Represents a point on the Earth
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coordinates {
+    /// Latitude
+    pub lat: f32,
+
+    /// Longitude
+    pub lon: f32,
+}
+
+impl Coordinates {
+    /// Get the great-circle distance to `other` in kilometers
+    /// using the [haversine formula](https://en.wikipedia.org/wiki/Haversine_formula)
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat, other_lat) = (
+            f64::from(self.lat).to_radians(),
+            f64::from(other.lat).to_radians(),
+        );
+        let delta_lat = other_lat - lat;
+        let delta_lon = f64::from(other.lon - self.lon).to_radians();
+
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat.cos() * other_lat.cos() * (delta_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+/// Represents how coarse the location returned by [`IpData::coarse_location`] is
+///
+/// The ground distances are approximate for latitude,
+/// the distances for longitude shrink towards the poles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoarsePrecision {
+    /// Whole degrees (about 111 km)
+    Country,
+
+    /// Half degrees (about 55 km)
+    Region,
+
+    /// Tenths of a degree (about 11 km)
+    City,
+}
+
+impl CoarsePrecision {
+    fn steps_per_degree(&self) -> f32 {
+        match self {
+            CoarsePrecision::Country => 1.0,
+            CoarsePrecision::Region => 2.0,
+            CoarsePrecision::City => 10.0,
+        }
+    }
+}
+
+/// Information about both public IP addresses of a dual-stack host
+///
+/// Returned by [`my_ips`]
+#[derive(Clone, Debug)]
+pub struct MyIps {
+    /// Information about the IPv4 address, if it's available
+    pub v4: Option<IpData>,
+
+    /// Information about the IPv6 address, if it's available
+    pub v6: Option<IpData>,
+}
+
+/// Represents the kind of [`region`](struct.IpData.html#structfield.region) short code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegionCodeKind {
+    /// Numeric FIPS-style code
+    ///
+    /// # Example
+    ///
+    /// 07
+    Fips,
+
+    /// Alphabetic (or alphanumeric) ISO 3166-2 subdivision code
+    ///
+    /// # Example
+    ///
+    /// QLD
+    Iso,
+}
+
+#[derive(Serialize)]
+struct BatchEntry<'a> {
+    query: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lang: Option<&'static str>,
+}
+
+impl<'a> BatchEntry<'a> {
+    fn new(query: &'a str) -> Self {
+        BatchEntry {
+            query,
+            fields: None,
+            lang: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct IpApiMessage {
+    message: Option<String>,
+}
+
+/// The data that will be received after the making a request
+///
+/// # Example response
+///
+/// ```rust
+/// # use ip_api_client::IpData;
+/// #
+/// IpData {
+///     continent: Some("Oceania".to_string()),
+///     continent_code: Some("OC".to_string()),
+///     country: Some("Australia".to_string()),
+///     country_code: Some("AU".to_string()),
+///     region: Some("QLD".to_string()),
+///     region_name: Some("Queensland".to_string()),
+///     city: Some("South Brisbane".to_string()),
+///     district: Some("".to_string()),
+///     zip: Some("4101".to_string()),
+///     lat: Some(-27.4766),
+///     lon: Some(153.0166),
+///     timezone: Some("Australia/Brisbane".to_string()),
+///     offset: Some(36000),
+///     currency: Some("AUD".to_string()),
+///     isp: Some("Cloudflare, Inc".to_string()),
+///     org: Some("APNIC and Cloudflare DNS Resolver project".to_string()),
+///     as_field: Some("AS13335 Cloudflare, Inc.".to_string()),
+///     asname: Some("CLOUDFLARENET".to_string()),
+///     reverse: Some("one.one.one.one".to_string()),
+///     mobile: Some(false),
+///     proxy: Some(false),
+///     hosting: Some(true),
+///     query: Some("1.1.1.1".to_string()),
+/// };
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpData {
+    /// Continent name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continent: Option<String>,
+
+    /// Two-letter continent code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continent_code: Option<String>,
+
+    /// Country name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+
+    /// Two-letter country code
+    /// [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+
+    /// Region/state short code (FIPS or ISO)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// Region/state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region_name: Option<String>,
+
+    /// City
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+
+    /// District (subdivision of city)
+    ///
+    /// An empty string if there is no district, see [`IpData::district_name`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub district: Option<String>,
+
+    /// Zip code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip: Option<String>,
+
+    /// Latitude
+    #[serde(
+        default,
+        deserialize_with = "deserialize_flexible_float",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lat: Option<f32>,
+
+    /// Longitude
+    #[serde(
+        default,
+        deserialize_with = "deserialize_flexible_float",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub lon: Option<f32>,
+
+    /// Timezone (tz)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+
+    /// Timezone UTC DST offset in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i32>,
+
+    /// National currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+
+    /// ISP name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isp: Option<String>,
+
+    /// Organization name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+
+    /// AS number and organization, separated by space (RIR).
+    /// Empty for IP blocks not being announced in BGP tables.
+    ///
+    /// # Notice
+    ///
+    /// We use `as_field` instead of `as`
+    /// (As stated in the [ip-api.com API documentation](https://ip-api.com/docs/api:json#as))
+    /// since it's a
+    /// [strict keyword](https://doc.rust-lang.org/reference/keywords.html#strict-keywords) in rust,
+    /// such as `pub`, `impl` or `struct`.
+    #[serde(rename = "as", skip_serializing_if = "Option::is_none")]
+    pub as_field: Option<String>,
+
+    /// AS name (RIR). Empty for IP blocks not being announced in BGP tables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asname: Option<String>,
+
+    /// Reverse DNS of the IP (can delay response)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverse: Option<String>,
+
+    /// Mobile (cellular) connection
+    #[serde(
+        default,
+        deserialize_with = "deserialize_flexible_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mobile: Option<bool>,
+
+    /// Proxy, VPN or Tor exit address
+    #[serde(
+        default,
+        deserialize_with = "deserialize_flexible_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub proxy: Option<bool>,
+
+    /// Hosting, colocated or data center
+    #[serde(
+        default,
+        deserialize_with = "deserialize_flexible_bool",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hosting: Option<bool>,
+
+    /// IP/Domain used for the query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+}
+
+/// Accept booleans stringified by intermediaries (`"true"`/`"false"`/`"1"`/`"0"`)
+/// as well as JSON booleans
+fn deserialize_flexible_bool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FlexibleBool {
+        Bool(bool),
+        String(String),
+    }
+
+    match Option::<FlexibleBool>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(FlexibleBool::Bool(value)) => Ok(Some(value)),
+        Some(FlexibleBool::String(value)) => match value.trim() {
+            "true" | "1" => Ok(Some(true)),
+            "false" | "0" => Ok(Some(false)),
+            value => Err(de::Error::invalid_value(
+                de::Unexpected::Str(value),
+                &"a boolean or one of \"true\", \"false\", \"1\", \"0\"",
+            )),
+        },
+    }
+}
+
+/// Accept numbers stringified by intermediaries (e.g. `"-27.47"`) as well as JSON numbers
+fn deserialize_flexible_float<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FlexibleFloat {
+        Float(f32),
+        String(String),
+    }
+
+    match Option::<FlexibleFloat>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(FlexibleFloat::Float(value)) => Ok(Some(value)),
+        Some(FlexibleFloat::String(value)) => match value.trim().parse::<f32>() {
+            Ok(value) if value.is_finite() => Ok(Some(value)),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Str(&value),
+                &"a number or a string with a number",
+            )),
+        },
+    }
+}
+
+impl IpData {
+    /// Build a [GeoURI](https://en.wikipedia.org/wiki/Geo_URI_scheme) (`geo:lat,lon`)
+    /// from [`lat`](struct.IpData.html#structfield.lat)
+    /// and [`lon`](struct.IpData.html#structfield.lon)
+    ///
+    /// Returns `None` if any of the coordinates is missing
+    pub fn geo_uri(&self) -> Option<String> {
+        Some(format!("geo:{},{}", self.lat?, self.lon?))
+    }
+
+    /// Get [`lat`](struct.IpData.html#structfield.lat)
+    /// and [`lon`](struct.IpData.html#structfield.lon) as [`Coordinates`]
+    ///
+    /// Returns `None` if any of the coordinates is missing
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        Some(Coordinates {
+            lat: self.lat?,
+            lon: self.lon?,
+        })
+    }
+
+    /// Get [`coordinates`](Self::coordinates) rounded to a grid of `precision`
+    ///
+    /// Useful for storing only a coarse location for privacy reasons.
+    /// Returns `None` if any of the coordinates is missing
+    pub fn coarse_location(&self, precision: CoarsePrecision) -> Option<Coordinates> {
+        let steps = precision.steps_per_degree();
+        let round = |value: f32| (value * steps).round() / steps;
+        let coordinates = self.coordinates()?;
+
+        Some(Coordinates {
+            lat: round(coordinates.lat),
+            lon: round(coordinates.lon),
+        })
+    }
+
+    fn fields(&self) -> [(IpDataField, Option<FieldValue<'_>>); 23] {
+        [
+            (
+                IpDataField::Continent,
+                self.continent.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::ContinentCode,
+                self.continent_code.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::Country,
+                self.country.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::CountryCode,
+                self.country_code.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::Region,
+                self.region.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::RegionName,
+                self.region_name.as_deref().map(FieldValue::Str),
+            ),
+            (IpDataField::City, self.city.as_deref().map(FieldValue::Str)),
+            (
+                IpDataField::District,
+                self.district.as_deref().map(FieldValue::Str),
+            ),
+            (IpDataField::Zip, self.zip.as_deref().map(FieldValue::Str)),
+            (IpDataField::Lat, self.lat.map(FieldValue::F32)),
+            (IpDataField::Lon, self.lon.map(FieldValue::F32)),
+            (
+                IpDataField::Timezone,
+                self.timezone.as_deref().map(FieldValue::Str),
+            ),
+            (IpDataField::Offset, self.offset.map(FieldValue::I32)),
+            (
+                IpDataField::Currency,
+                self.currency.as_deref().map(FieldValue::Str),
+            ),
+            (IpDataField::Isp, self.isp.as_deref().map(FieldValue::Str)),
+            (IpDataField::Org, self.org.as_deref().map(FieldValue::Str)),
+            (
+                IpDataField::AsField,
+                self.as_field.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::Asname,
+                self.asname.as_deref().map(FieldValue::Str),
+            ),
+            (
+                IpDataField::Reverse,
+                self.reverse.as_deref().map(FieldValue::Str),
+            ),
+            (IpDataField::Mobile, self.mobile.map(FieldValue::Bool)),
+            (IpDataField::Proxy, self.proxy.map(FieldValue::Bool)),
+            (IpDataField::Hosting, self.hosting.map(FieldValue::Bool)),
+            (
+                IpDataField::Query,
+                self.query.as_deref().map(FieldValue::Str),
+            ),
+        ]
+    }
+
+    /// Iterate over all received fields as `(name, value)` pairs
+    ///
+    /// Names are the field names used in the
+    /// [ip-api.com API](https://ip-api.com/docs/api:json#returned-data) JSON response
+    /// (see [`IpDataField::name`]). Fields are always yielded in the order of [`IpData`] fields,
+    /// missing fields are skipped
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, FieldValue<'_>)> {
+        self.fields()
+            .into_iter()
+            .filter_map(|(field, value)| Some((field.name(), value?)))
+    }
+
+    /// Get all fields whose values differ in `other`
+    ///
+    /// Useful for detecting changes when re-checking an IP over time.
+    /// Fields missing on any side are skipped (they may be just not requested),
+    /// so only the fields received in both [`IpData`] are compared
+    pub fn diff(&self, other: &IpData) -> Vec<FieldDiff> {
+        self.fields()
+            .into_iter()
+            .zip(other.fields())
+            .filter_map(|((field, old), (_, new))| match (old?, new?) {
+                (old, new) if old != new => Some(FieldDiff {
+                    field,
+                    old: Some(old.to_string()),
+                    new: Some(new.to_string()),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the fraction (from `0.0` to `1.0`) of the fields requested by `config`
+    /// that are present and non-empty
+    ///
+    /// [`IpDataField::Query`] and [`IpDataField::Message`] are not counted.
+    /// Returns `1.0` if no other fields are requested
+    pub fn completeness(&self, config: &IpApiConfig) -> f32 {
+        let (requested, populated) = self
+            .fields()
+            .into_iter()
+            .filter(|(field, _)| {
+                *field != IpDataField::Query && config.numeric_field & field.bit() != 0
+            })
+            .fold((0, 0), |(requested, populated), (_, value)| {
+                let is_populated = match value {
+                    Some(FieldValue::Str(value)) => !value.is_empty(),
+                    value => value.is_some(),
+                };
+
+                (requested + 1, populated + usize::from(is_populated))
+            });
+
+        if requested == 0 {
+            return 1.0;
+        }
+
+        populated as f32 / requested as f32
+    }
+
+    /// Build a [GeoJSON](https://geojson.org/) `Feature` with a `Point`
+    /// from [`lat`](struct.IpData.html#structfield.lat)
+    /// and [`lon`](struct.IpData.html#structfield.lon)
+    ///
+    /// Other received fields are added to `properties`.
+    /// Returns `None` if any of the coordinates is missing
+    pub fn to_geojson(&self) -> Option<serde_json::Value> {
+        let coordinates = self.coordinates()?;
+        let properties = IpData {
+            lat: None,
+            lon: None,
+            ..self.clone()
+        };
+
+        Some(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [coordinates.lon, coordinates.lat],
+            },
+            "properties": properties,
+        }))
+    }
+
+    /// Serialize all received fields to a pretty-printed JSON
+    ///
+    /// Missing fields are skipped
+    pub fn to_pretty_json(&self) -> String {
+        // Serialization of `IpData` can't fail,
+        // since it contains only strings, numbers and booleans
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Convert all received fields to a map of JSON values sorted by the keys
+    ///
+    /// The keys are the names used in the
+    /// [ip-api.com API](https://ip-api.com/docs/api:json#returned-data) JSON response
+    /// (e.g. `countryCode` or `as` for [`as_field`](struct.IpData.html#structfield.as_field)).
+    /// Unlike [`to_string_map`](Self::to_string_map) the values keep their types,
+    /// and the order is deterministic, which is handy for snapshot tests.
+    /// Missing fields are skipped
+    pub fn to_sorted_map(&self) -> BTreeMap<String, serde_json::Value> {
+        self.iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    FieldValue::Str(value) => json!(value),
+                    FieldValue::F32(value) => json!(value),
+                    FieldValue::I32(value) => json!(value),
+                    FieldValue::Bool(value) => json!(value),
+                };
+
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    /// Convert all received fields to a map of strings
+    ///
+    /// Keys are the field names used in the
+    /// [ip-api.com API](https://ip-api.com/docs/api:json#returned-data) JSON response
+    /// (e.g. `countryCode` or `as` for [`as_field`](struct.IpData.html#structfield.as_field)).
+    /// Missing fields are skipped
+    pub fn to_string_map(&self) -> HashMap<String, String> {
+        self.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// Check if both IPs are located in the same country
+    ///
+    /// Compares [`country_code`](struct.IpData.html#structfield.country_code)
+    /// (unlike [`country`](struct.IpData.html#structfield.country)
+    /// it doesn't depend on the language).
+    /// Returns `None` if any of the IPs lacks the field
+    pub fn same_country_as(&self, other: &IpData) -> Option<bool> {
+        Some(self.country_code.as_ref()? == other.country_code.as_ref()?)
+    }
+
+    fn continent_code_of(country_code: &str) -> Option<&'static str> {
+        let continent_code = match country_code.to_ascii_uppercase().as_str() {
+            "AO" | "BF" | "BI" | "BJ" | "BW" | "CD" | "CF" | "CG" | "CI" | "CM" | "CV" | "DJ"
+            | "DZ" | "EG" | "EH" | "ER" | "ET" | "GA" | "GH" | "GM" | "GN" | "GQ" | "GW" | "KE"
+            | "KM" | "LR" | "LS" | "LY" | "MA" | "MG" | "ML" | "MR" | "MU" | "MW" | "MZ" | "NA"
+            | "NE" | "NG" | "RE" | "RW" | "SC" | "SD" | "SH" | "SL" | "SN" | "SO" | "SS" | "ST"
+            | "SZ" | "TD" | "TG" | "TN" | "TZ" | "UG" | "YT" | "ZA" | "ZM" | "ZW" => "AF",
+            "AQ" | "BV" | "GS" | "HM" | "TF" => "AN",
+            "AE" | "AF" | "AM" | "AZ" | "BD" | "BH" | "BN" | "BT" | "CC" | "CN" | "CX" | "GE"
+            | "HK" | "ID" | "IL" | "IN" | "IO" | "IQ" | "IR" | "JO" | "JP" | "KG" | "KH" | "KP"
+            | "KR" | "KW" | "KZ" | "LA" | "LB" | "LK" | "MM" | "MN" | "MO" | "MV" | "MY" | "NP"
+            | "OM" | "PH" | "PK" | "PS" | "QA" | "SA" | "SG" | "SY" | "TH" | "TJ" | "TL" | "TM"
+            | "TR" | "TW" | "UZ" | "VN" | "YE" => "AS",
+            "AD" | "AL" | "AT" | "AX" | "BA" | "BE" | "BG" | "BY" | "CH" | "CY" | "CZ" | "DE"
+            | "DK" | "EE" | "ES" | "FI" | "FO" | "FR" | "GB" | "GG" | "GI" | "GR" | "HR" | "HU"
+            | "IE" | "IM" | "IS" | "IT" | "JE" | "LI" | "LT" | "LU" | "LV" | "MC" | "MD" | "ME"
+            | "MK" | "MT" | "NL" | "NO" | "PL" | "PT" | "RO" | "RS" | "RU" | "SE" | "SI" | "SJ"
+            | "SK" | "SM" | "UA" | "VA" | "XK" => "EU",
+            "AG" | "AI" | "AW" | "BB" | "BL" | "BM" | "BQ" | "BS" | "BZ" | "CA" | "CR" | "CU"
+            | "CW" | "DM" | "DO" | "GD" | "GL" | "GP" | "GT" | "HN" | "HT" | "JM" | "KN" | "KY"
+            | "LC" | "MF" | "MQ" | "MS" | "MX" | "NI" | "PA" | "PM" | "PR" | "SV" | "SX" | "TC"
+            | "TT" | "US" | "VC" | "VG" | "VI" => "NA",
+            "AS" | "AU" | "CK" | "FJ" | "FM" | "GU" | "KI" | "MH" | "MP" | "NC" | "NF" | "NR"
+            | "NU" | "NZ" | "PF" | "PG" | "PN" | "PW" | "SB" | "TK" | "TO" | "TV" | "UM" | "VU"
+            | "WF" | "WS" => "OC",
+            "AR" | "BO" | "BR" | "CL" | "CO" | "EC" | "FK" | "GF" | "GY" | "PE" | "PY" | "SR"
+            | "UY" | "VE" => "SA",
+            _ => return None,
+        };
+
+        Some(continent_code)
+    }
+
+    /// Get the English name of the continent from
+    /// [`continent_code`](struct.IpData.html#structfield.continent_code)
+    ///
+    /// Unlike [`continent`](struct.IpData.html#structfield.continent) it doesn't depend
+    /// on the language, so it's a stable key for grouping results in different languages.
+    /// Returns `None` if the field is missing or the code is unknown
+    pub fn continent_en(&self) -> Option<&'static str> {
+        let continent = match self.continent_code.as_deref()?.trim() {
+            "AF" => "Africa",
+            "AN" => "Antarctica",
+            "AS" => "Asia",
+            "EU" => "Europe",
+            "NA" => "North America",
+            "OC" => "Oceania",
+            "SA" => "South America",
+            _ => return None,
+        };
+
+        Some(continent)
+    }
+
+    /// Get a single address line like `South Brisbane, QLD 4101, Australia`
+    ///
+    /// Consists of [`city`](struct.IpData.html#structfield.city),
+    /// [`region`](struct.IpData.html#structfield.region) (or
+    /// [`region_name`](struct.IpData.html#structfield.region_name) if it's missing)
+    /// followed by [`zip`](struct.IpData.html#structfield.zip), and
+    /// [`country`](struct.IpData.html#structfield.country).
+    /// Missing and empty parts are skipped, returns `None` if all of them are missing
+    pub fn formatted_address(&self) -> Option<String> {
+        fn part(value: &Option<String>) -> Option<&str> {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        }
+
+        let region = part(&self.region).or_else(|| part(&self.region_name));
+        let region_zip = match (region, part(&self.zip)) {
+            (Some(region), Some(zip)) => Some(format!("{region} {zip}")),
+            (region, zip) => region.or(zip).map(String::from),
+        };
+
+        let parts: Vec<String> = [
+            part(&self.city).map(String::from),
+            region_zip,
+            part(&self.country).map(String::from),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        Some(parts.join(", "))
+    }
+
+    /// Check if [`country_code`](struct.IpData.html#structfield.country_code)
+    /// belongs to [`continent_code`](struct.IpData.html#structfield.continent_code)
+    ///
+    /// Uses a built-in country to continent table, so a mismatch points to
+    /// a suspect record. Transcontinental countries are assigned to one continent
+    /// (e.g. Russia to Europe and Turkey to Asia).
+    /// Returns `None` if any of the fields is missing or the country is unknown
+    pub fn is_geographically_consistent(&self) -> Option<bool> {
+        let continent_code = Self::continent_code_of(self.country_code.as_deref()?)?;
+
+        Some(continent_code.eq_ignore_ascii_case(self.continent_code.as_deref()?))
+    }
+
+    /// Check if both IPs are located in the same region of the same country
+    ///
+    /// Compares [`country_code`](struct.IpData.html#structfield.country_code)
+    /// and [`region`](struct.IpData.html#structfield.region),
+    /// since region codes may repeat in different countries.
+    /// Returns `None` if any of the IPs lacks these fields
+    pub fn same_region_as(&self, other: &IpData) -> Option<bool> {
+        let same_region = self.region.as_ref()? == other.region.as_ref()?;
+
+        Some(self.same_country_as(other)? && same_region)
+    }
+
+    /// Check if both IPs are located in the same city of the same country
+    ///
+    /// Compares [`country_code`](struct.IpData.html#structfield.country_code)
+    /// and [`city`](struct.IpData.html#structfield.city).
+    /// Returns `None` if any of the IPs lacks these fields
+    ///
+    /// # Notice
+    ///
+    /// City names are localized, so both IPs should be requested with the same language
+    pub fn same_city_as(&self, other: &IpData) -> Option<bool> {
+        let same_city = self.city.as_ref()? == other.city.as_ref()?;
+
+        Some(self.same_country_as(other)? && same_city)
+    }
+
+    /// Get the most specific place name available
+    ///
+    /// Returns the first non-empty field of [`city`](struct.IpData.html#structfield.city),
+    /// [`region_name`](struct.IpData.html#structfield.region_name)
+    /// and [`country`](struct.IpData.html#structfield.country)
+    pub fn best_place_name(&self) -> Option<&str> {
+        [&self.city, &self.region_name, &self.country]
+            .into_iter()
+            .filter_map(|name| name.as_deref())
+            .find(|name| !name.is_empty())
+    }
+
+    /// Build a Google Maps link to the location from [`lat`](struct.IpData.html#structfield.lat)
+    /// and [`lon`](struct.IpData.html#structfield.lon)
+    ///
+    /// Returns `None` if any of the coordinates is missing
+    pub fn maps_url(&self) -> Option<String> {
+        Some(format!("{MAPS_SEARCH_URL}{},{}", self.lat?, self.lon?))
+    }
+
+    /// Get the current local time at the IP using [`offset`](struct.IpData.html#structfield.offset)
+    ///
+    /// The offset is fixed at the moment of the request,
+    /// so the result may be wrong if a DST transition has happened since then.
+    /// Returns `None` if the offset is missing
+    #[cfg(feature = "chrono")]
+    pub fn current_local_time(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        let offset = chrono::FixedOffset::east_opt(self.offset?)?;
+
+        Some(chrono::Utc::now().with_timezone(&offset))
+    }
+
+    /// Get [`district`](struct.IpData.html#structfield.district)
+    /// treating an empty string as missing
+    ///
+    /// [ip-api.com API](https://ip-api.com/docs/api:json) returns an empty string instead
+    /// of omitting the field if there is no district. The same convention is used for
+    /// [`as_field`](struct.IpData.html#structfield.as_field),
+    /// [`asname`](struct.IpData.html#structfield.asname)
+    /// and [`reverse`](struct.IpData.html#structfield.reverse)
+    pub fn district_name(&self) -> Option<&str> {
+        self.district
+            .as_deref()
+            .filter(|district| !district.is_empty())
+    }
+
+    /// Get the current abbreviation of [`timezone`](struct.IpData.html#structfield.timezone)
+    ///
+    /// # Example
+    ///
+    /// `AEST` for `Australia/Brisbane`
+    ///
+    /// # Notice
+    ///
+    /// The abbreviation depends on the current date due to DST
+    /// (e.g. `CET` in winter and `CEST` in summer for `Europe/Berlin`).
+    /// Returns `None` if the timezone is missing or unknown
+    #[cfg(feature = "chrono-tz")]
+    pub fn timezone_abbreviation(&self) -> Option<String> {
+        let timezone: chrono_tz::Tz = self.timezone.as_deref()?.parse().ok()?;
+
+        Some(
+            chrono::Utc::now()
+                .with_timezone(&timezone)
+                .format("%Z")
+                .to_string(),
+        )
+    }
+
+    #[cfg(all(feature = "isocountry", feature = "chrono-tz"))]
+    fn timezone_country_code(timezone: &str) -> Option<&'static str> {
+        // A snapshot of `zone.tab` of the IANA time zone database, version 2025b
+        let country_code = match timezone {
+            "Europe/Andorra" => "AD",
+            "Asia/Dubai" => "AE",
+            "Asia/Kabul" => "AF",
+            "America/Antigua" => "AG",
+            "America/Anguilla" => "AI",
+            "Europe/Tirane" => "AL",
+            "Asia/Yerevan" => "AM",
+            "Africa/Luanda" => "AO",
+            "Antarctica/McMurdo"
+            | "Antarctica/Casey"
+            | "Antarctica/Davis"
+            | "Antarctica/DumontDUrville"
+            | "Antarctica/Mawson"
+            | "Antarctica/Palmer"
+            | "Antarctica/Rothera"
+            | "Antarctica/Syowa"
+            | "Antarctica/Troll"
+            | "Antarctica/Vostok" => "AQ",
+            "America/Argentina/Buenos_Aires"
+            | "America/Argentina/Cordoba"
+            | "America/Argentina/Salta"
+            | "America/Argentina/Jujuy"
+            | "America/Argentina/Tucuman"
+            | "America/Argentina/Catamarca"
+            | "America/Argentina/La_Rioja"
+            | "America/Argentina/San_Juan"
+            | "America/Argentina/Mendoza"
+            | "America/Argentina/San_Luis"
+            | "America/Argentina/Rio_Gallegos"
+            | "America/Argentina/Ushuaia" => "AR",
+            "Pacific/Pago_Pago" => "AS",
+            "Europe/Vienna" => "AT",
+            "Australia/Lord_Howe"
+            | "Antarctica/Macquarie"
+            | "Australia/Hobart"
+            | "Australia/Melbourne"
+            | "Australia/Sydney"
+            | "Australia/Broken_Hill"
+            | "Australia/Brisbane"
+            | "Australia/Lindeman"
+            | "Australia/Adelaide"
+            | "Australia/Darwin"
+            | "Australia/Perth"
+            | "Australia/Eucla" => "AU",
+            "America/Aruba" => "AW",
+            "Europe/Mariehamn" => "AX",
+            "Asia/Baku" => "AZ",
+            "Europe/Sarajevo" => "BA",
+            "America/Barbados" => "BB",
+            "Asia/Dhaka" => "BD",
+            "Europe/Brussels" => "BE",
+            "Africa/Ouagadougou" => "BF",
+            "Europe/Sofia" => "BG",
+            "Asia/Bahrain" => "BH",
+            "Africa/Bujumbura" => "BI",
+            "Africa/Porto-Novo" => "BJ",
+            "America/St_Barthelemy" => "BL",
+            "Atlantic/Bermuda" => "BM",
+            "Asia/Brunei" => "BN",
+            "America/La_Paz" => "BO",
+            "America/Kralendijk" => "BQ",
+            "America/Noronha"
+            | "America/Belem"
+            | "America/Fortaleza"
+            | "America/Recife"
+            | "America/Araguaina"
+            | "America/Maceio"
+            | "America/Bahia"
+            | "America/Sao_Paulo"
+            | "America/Campo_Grande"
+            | "America/Cuiaba"
+            | "America/Santarem"
+            | "America/Porto_Velho"
+            | "America/Boa_Vista"
+            | "America/Manaus"
+            | "America/Eirunepe"
+            | "America/Rio_Branco" => "BR",
+            "America/Nassau" => "BS",
+            "Asia/Thimphu" => "BT",
+            "Africa/Gaborone" => "BW",
+            "Europe/Minsk" => "BY",
+            "America/Belize" => "BZ",
+            "America/St_Johns"
+            | "America/Halifax"
+            | "America/Glace_Bay"
+            | "America/Moncton"
+            | "America/Goose_Bay"
+            | "America/Blanc-Sablon"
+            | "America/Toronto"
+            | "America/Iqaluit"
+            | "America/Atikokan"
+            | "America/Winnipeg"
+            | "America/Resolute"
+            | "America/Rankin_Inlet"
+            | "America/Regina"
+            | "America/Swift_Current"
+            | "America/Edmonton"
+            | "America/Cambridge_Bay"
+            | "America/Inuvik"
+            | "America/Creston"
+            | "America/Dawson_Creek"
+            | "America/Fort_Nelson"
+            | "America/Whitehorse"
+            | "America/Dawson"
+            | "America/Vancouver" => "CA",
+            "Indian/Cocos" => "CC",
+            "Africa/Kinshasa" | "Africa/Lubumbashi" => "CD",
+            "Africa/Bangui" => "CF",
+            "Africa/Brazzaville" => "CG",
+            "Europe/Zurich" => "CH",
+            "Africa/Abidjan" => "CI",
+            "Pacific/Rarotonga" => "CK",
+            "America/Santiago"
+            | "America/Coyhaique"
+            | "America/Punta_Arenas"
+            | "Pacific/Easter" => "CL",
+            "Africa/Douala" => "CM",
+            "Asia/Shanghai" | "Asia/Urumqi" => "CN",
+            "America/Bogota" => "CO",
+            "America/Costa_Rica" => "CR",
+            "America/Havana" => "CU",
+            "Atlantic/Cape_Verde" => "CV",
+            "America/Curacao" => "CW",
+            "Indian/Christmas" => "CX",
+            "Asia/Nicosia" | "Asia/Famagusta" => "CY",
+            "Europe/Prague" => "CZ",
+            "Europe/Berlin" | "Europe/Busingen" => "DE",
+            "Africa/Djibouti" => "DJ",
+            "Europe/Copenhagen" => "DK",
+            "America/Dominica" => "DM",
+            "America/Santo_Domingo" => "DO",
+            "Africa/Algiers" => "DZ",
+            "America/Guayaquil" | "Pacific/Galapagos" => "EC",
+            "Europe/Tallinn" => "EE",
+            "Africa/Cairo" => "EG",
+            "Africa/El_Aaiun" => "EH",
+            "Africa/Asmara" => "ER",
+            "Europe/Madrid" | "Africa/Ceuta" | "Atlantic/Canary" => "ES",
+            "Africa/Addis_Ababa" => "ET",
+            "Europe/Helsinki" => "FI",
+            "Pacific/Fiji" => "FJ",
+            "Atlantic/Stanley" => "FK",
+            "Pacific/Chuuk" | "Pacific/Pohnpei" | "Pacific/Kosrae" => "FM",
+            "Atlantic/Faroe" => "FO",
+            "Europe/Paris" => "FR",
+            "Africa/Libreville" => "GA",
+            "Europe/London" => "GB",
+            "America/Grenada" => "GD",
+            "Asia/Tbilisi" => "GE",
+            "America/Cayenne" => "GF",
+            "Europe/Guernsey" => "GG",
+            "Africa/Accra" => "GH",
+            "Europe/Gibraltar" => "GI",
+            "America/Nuuk" | "America/Danmarkshavn" | "America/Scoresbysund" | "America/Thule" => {
+                "GL"
+            }
+            "Africa/Banjul" => "GM",
+            "Africa/Conakry" => "GN",
+            "America/Guadeloupe" => "GP",
+            "Africa/Malabo" => "GQ",
+            "Europe/Athens" => "GR",
+            "Atlantic/South_Georgia" => "GS",
+            "America/Guatemala" => "GT",
+            "Pacific/Guam" => "GU",
+            "Africa/Bissau" => "GW",
+            "America/Guyana" => "GY",
+            "Asia/Hong_Kong" => "HK",
+            "America/Tegucigalpa" => "HN",
+            "Europe/Zagreb" => "HR",
+            "America/Port-au-Prince" => "HT",
+            "Europe/Budapest" => "HU",
+            "Asia/Jakarta" | "Asia/Pontianak" | "Asia/Makassar" | "Asia/Jayapura" => "ID",
+            "Europe/Dublin" => "IE",
+            "Asia/Jerusalem" => "IL",
+            "Europe/Isle_of_Man" => "IM",
+            "Asia/Kolkata" => "IN",
+            "Indian/Chagos" => "IO",
+            "Asia/Baghdad" => "IQ",
+            "Asia/Tehran" => "IR",
+            "Atlantic/Reykjavik" => "IS",
+            "Europe/Rome" => "IT",
+            "Europe/Jersey" => "JE",
+            "America/Jamaica" => "JM",
+            "Asia/Amman" => "JO",
+            "Asia/Tokyo" => "JP",
+            "Africa/Nairobi" => "KE",
+            "Asia/Bishkek" => "KG",
+            "Asia/Phnom_Penh" => "KH",
+            "Pacific/Tarawa" | "Pacific/Kanton" | "Pacific/Kiritimati" => "KI",
+            "Indian/Comoro" => "KM",
+            "America/St_Kitts" => "KN",
+            "Asia/Pyongyang" => "KP",
+            "Asia/Seoul" => "KR",
+            "Asia/Kuwait" => "KW",
+            "America/Cayman" => "KY",
+            "Asia/Almaty" | "Asia/Qyzylorda" | "Asia/Qostanay" | "Asia/Aqtobe" | "Asia/Aqtau"
+            | "Asia/Atyrau" | "Asia/Oral" => "KZ",
+            "Asia/Vientiane" => "LA",
+            "Asia/Beirut" => "LB",
+            "America/St_Lucia" => "LC",
+            "Europe/Vaduz" => "LI",
+            "Asia/Colombo" => "LK",
+            "Africa/Monrovia" => "LR",
+            "Africa/Maseru" => "LS",
+            "Europe/Vilnius" => "LT",
+            "Europe/Luxembourg" => "LU",
+            "Europe/Riga" => "LV",
+            "Africa/Tripoli" => "LY",
+            "Africa/Casablanca" => "MA",
+            "Europe/Monaco" => "MC",
+            "Europe/Chisinau" => "MD",
+            "Europe/Podgorica" => "ME",
+            "America/Marigot" => "MF",
+            "Indian/Antananarivo" => "MG",
+            "Pacific/Majuro" | "Pacific/Kwajalein" => "MH",
+            "Europe/Skopje" => "MK",
+            "Africa/Bamako" => "ML",
+            "Asia/Yangon" => "MM",
+            "Asia/Ulaanbaatar" | "Asia/Hovd" => "MN",
+            "Asia/Macau" => "MO",
+            "Pacific/Saipan" => "MP",
+            "America/Martinique" => "MQ",
+            "Africa/Nouakchott" => "MR",
+            "America/Montserrat" => "MS",
+            "Europe/Malta" => "MT",
+            "Indian/Mauritius" => "MU",
+            "Indian/Maldives" => "MV",
+            "Africa/Blantyre" => "MW",
+            "America/Mexico_City"
+            | "America/Cancun"
+            | "America/Merida"
+            | "America/Monterrey"
+            | "America/Matamoros"
+            | "America/Chihuahua"
+            | "America/Ciudad_Juarez"
+            | "America/Ojinaga"
+            | "America/Mazatlan"
+            | "America/Bahia_Banderas"
+            | "America/Hermosillo"
+            | "America/Tijuana" => "MX",
+            "Asia/Kuala_Lumpur" | "Asia/Kuching" => "MY",
+            "Africa/Maputo" => "MZ",
+            "Africa/Windhoek" => "NA",
+            "Pacific/Noumea" => "NC",
+            "Africa/Niamey" => "NE",
+            "Pacific/Norfolk" => "NF",
+            "Africa/Lagos" => "NG",
+            "America/Managua" => "NI",
+            "Europe/Amsterdam" => "NL",
+            "Europe/Oslo" => "NO",
+            "Asia/Kathmandu" => "NP",
+            "Pacific/Nauru" => "NR",
+            "Pacific/Niue" => "NU",
+            "Pacific/Auckland" | "Pacific/Chatham" => "NZ",
+            "Asia/Muscat" => "OM",
+            "America/Panama" => "PA",
+            "America/Lima" => "PE",
+            "Pacific/Tahiti" | "Pacific/Marquesas" | "Pacific/Gambier" => "PF",
+            "Pacific/Port_Moresby" | "Pacific/Bougainville" => "PG",
+            "Asia/Manila" => "PH",
+            "Asia/Karachi" => "PK",
+            "Europe/Warsaw" => "PL",
+            "America/Miquelon" => "PM",
+            "Pacific/Pitcairn" => "PN",
+            "America/Puerto_Rico" => "PR",
+            "Asia/Gaza" | "Asia/Hebron" => "PS",
+            "Europe/Lisbon" | "Atlantic/Madeira" | "Atlantic/Azores" => "PT",
+            "Pacific/Palau" => "PW",
+            "America/Asuncion" => "PY",
+            "Asia/Qatar" => "QA",
+            "Indian/Reunion" => "RE",
+            "Europe/Bucharest" => "RO",
+            "Europe/Belgrade" => "RS",
+            "Europe/Kaliningrad" | "Europe/Moscow" | "Europe/Kirov" | "Europe/Volgograd"
+            | "Europe/Astrakhan" | "Europe/Saratov" | "Europe/Ulyanovsk" | "Europe/Samara"
+            | "Asia/Yekaterinburg" | "Asia/Omsk" | "Asia/Novosibirsk" | "Asia/Barnaul"
+            | "Asia/Tomsk" | "Asia/Novokuznetsk" | "Asia/Krasnoyarsk" | "Asia/Irkutsk"
+            | "Asia/Chita" | "Asia/Yakutsk" | "Asia/Khandyga" | "Asia/Vladivostok"
+            | "Asia/Ust-Nera" | "Asia/Magadan" | "Asia/Sakhalin" | "Asia/Srednekolymsk"
+            | "Asia/Kamchatka" | "Asia/Anadyr" => "RU",
+            "Africa/Kigali" => "RW",
+            "Asia/Riyadh" => "SA",
+            "Pacific/Guadalcanal" => "SB",
+            "Indian/Mahe" => "SC",
+            "Africa/Khartoum" => "SD",
+            "Europe/Stockholm" => "SE",
+            "Asia/Singapore" => "SG",
+            "Atlantic/St_Helena" => "SH",
+            "Europe/Ljubljana" => "SI",
+            "Arctic/Longyearbyen" => "SJ",
+            "Europe/Bratislava" => "SK",
+            "Africa/Freetown" => "SL",
+            "Europe/San_Marino" => "SM",
+            "Africa/Dakar" => "SN",
+            "Africa/Mogadishu" => "SO",
+            "America/Paramaribo" => "SR",
+            "Africa/Juba" => "SS",
+            "Africa/Sao_Tome" => "ST",
+            "America/El_Salvador" => "SV",
+            "America/Lower_Princes" => "SX",
+            "Asia/Damascus" => "SY",
+            "Africa/Mbabane" => "SZ",
+            "America/Grand_Turk" => "TC",
+            "Africa/Ndjamena" => "TD",
+            "Indian/Kerguelen" => "TF",
+            "Africa/Lome" => "TG",
+            "Asia/Bangkok" => "TH",
+            "Asia/Dushanbe" => "TJ",
+            "Pacific/Fakaofo" => "TK",
+            "Asia/Dili" => "TL",
+            "Asia/Ashgabat" => "TM",
+            "Africa/Tunis" => "TN",
+            "Pacific/Tongatapu" => "TO",
+            "Europe/Istanbul" => "TR",
+            "America/Port_of_Spain" => "TT",
+            "Pacific/Funafuti" => "TV",
+            "Asia/Taipei" => "TW",
+            "Africa/Dar_es_Salaam" => "TZ",
+            "Europe/Simferopol" | "Europe/Kyiv" => "UA",
+            "Africa/Kampala" => "UG",
+            "Pacific/Midway" | "Pacific/Wake" => "UM",
+            "America/New_York"
+            | "America/Detroit"
+            | "America/Kentucky/Louisville"
+            | "America/Kentucky/Monticello"
+            | "America/Indiana/Indianapolis"
+            | "America/Indiana/Vincennes"
+            | "America/Indiana/Winamac"
+            | "America/Indiana/Marengo"
+            | "America/Indiana/Petersburg"
+            | "America/Indiana/Vevay"
+            | "America/Chicago"
+            | "America/Indiana/Tell_City"
+            | "America/Indiana/Knox"
+            | "America/Menominee"
+            | "America/North_Dakota/Center"
+            | "America/North_Dakota/New_Salem"
+            | "America/North_Dakota/Beulah"
+            | "America/Denver"
+            | "America/Boise"
+            | "America/Phoenix"
+            | "America/Los_Angeles"
+            | "America/Anchorage"
+            | "America/Juneau"
+            | "America/Sitka"
+            | "America/Metlakatla"
+            | "America/Yakutat"
+            | "America/Nome"
+            | "America/Adak"
+            | "Pacific/Honolulu" => "US",
+            "America/Montevideo" => "UY",
+            "Asia/Samarkand" | "Asia/Tashkent" => "UZ",
+            "Europe/Vatican" => "VA",
+            "America/St_Vincent" => "VC",
+            "America/Caracas" => "VE",
+            "America/Tortola" => "VG",
+            "America/St_Thomas" => "VI",
+            "Asia/Ho_Chi_Minh" => "VN",
+            "Pacific/Efate" => "VU",
+            "Pacific/Wallis" => "WF",
+            "Pacific/Apia" => "WS",
+            "Asia/Aden" => "YE",
+            "Indian/Mayotte" => "YT",
+            "Africa/Johannesburg" => "ZA",
+            "Africa/Lusaka" => "ZM",
+            "Africa/Harare" => "ZW",
+            _ => return None,
+        };
+
+        Some(country_code)
+    }
+
+    #[cfg(all(feature = "isocountry", feature = "chrono-tz"))]
+    fn canonical_timezone(timezone: &str) -> Option<&'static str> {
+        // Links of `backward` of the IANA time zone database, version 2025b
+        // (the one bundled with chrono-tz), except the legacy zones that aren't bound
+        // to a location (e.g. `CET`) and the links to zones of other countries
+        // (e.g. `Atlantic/Jan_Mayen` to `Europe/Berlin`)
+        let canonical = match timezone {
+            "Africa/Asmera" => "Africa/Asmara",
+            "Egypt" => "Africa/Cairo",
+            "Libya" => "Africa/Tripoli",
+            "America/Atka" | "US/Aleutian" => "America/Adak",
+            "US/Alaska" => "America/Anchorage",
+            "America/Buenos_Aires" => "America/Argentina/Buenos_Aires",
+            "America/Argentina/ComodRivadavia" | "America/Catamarca" => {
+                "America/Argentina/Catamarca"
+            }
+            "America/Cordoba" | "America/Rosario" => "America/Argentina/Cordoba",
+            "America/Jujuy" => "America/Argentina/Jujuy",
+            "America/Mendoza" => "America/Argentina/Mendoza",
+            "US/Central" => "America/Chicago",
+            "America/Shiprock" | "Navajo" | "US/Mountain" => "America/Denver",
+            "US/Michigan" => "America/Detroit",
+            "America/Yellowknife" | "Canada/Mountain" => "America/Edmonton",
+            "Canada/Atlantic" => "America/Halifax",
+            "Cuba" => "America/Havana",
+            "America/Fort_Wayne" | "America/Indianapolis" | "US/East-Indiana" => {
+                "America/Indiana/Indianapolis"
+            }
+            "America/Knox_IN" | "US/Indiana-Starke" => "America/Indiana/Knox",
+            "America/Pangnirtung" => "America/Iqaluit",
+            "Jamaica" => "America/Jamaica",
+            "America/Louisville" => "America/Kentucky/Louisville",
+            "US/Pacific" => "America/Los_Angeles",
+            "Brazil/West" => "America/Manaus",
+            "Mexico/BajaSur" => "America/Mazatlan",
+            "Mexico/General" => "America/Mexico_City",
+            "US/Eastern" => "America/New_York",
+            "Brazil/DeNoronha" => "America/Noronha",
+            "America/Godthab" => "America/Nuuk",
+            "US/Arizona" => "America/Phoenix",
+            "Canada/Saskatchewan" => "America/Regina",
+            "America/Porto_Acre" | "Brazil/Acre" => "America/Rio_Branco",
+            "Chile/Continental" => "America/Santiago",
+            "Brazil/East" => "America/Sao_Paulo",
+            "Canada/Newfoundland" => "America/St_Johns",
+            "America/Virgin" => "America/St_Thomas",
+            "America/Ensenada" | "America/Santa_Isabel" | "Mexico/BajaNorte" => "America/Tijuana",
+            "America/Montreal" | "America/Nipigon" | "America/Thunder_Bay" | "Canada/Eastern" => {
+                "America/Toronto"
+            }
+            "Canada/Pacific" => "America/Vancouver",
+            "Canada/Yukon" => "America/Whitehorse",
+            "America/Rainy_River" | "Canada/Central" => "America/Winnipeg",
+            "Asia/Ashkhabad" => "Asia/Ashgabat",
+            "Asia/Dacca" => "Asia/Dhaka",
+            "Asia/Saigon" => "Asia/Ho_Chi_Minh",
+            "Hongkong" => "Asia/Hong_Kong",
+            "Asia/Tel_Aviv" | "Israel" => "Asia/Jerusalem",
+            "Asia/Katmandu" => "Asia/Kathmandu",
+            "Asia/Calcutta" => "Asia/Kolkata",
+            "Asia/Macao" => "Asia/Macau",
+            "Asia/Ujung_Pandang" => "Asia/Makassar",
+            "Europe/Nicosia" => "Asia/Nicosia",
+            "ROK" => "Asia/Seoul",
+            "Asia/Chongqing" | "Asia/Chungking" | "Asia/Harbin" | "PRC" => "Asia/Shanghai",
+            "Singapore" => "Asia/Singapore",
+            "ROC" => "Asia/Taipei",
+            "Iran" => "Asia/Tehran",
+            "Asia/Thimbu" => "Asia/Thimphu",
+            "Japan" => "Asia/Tokyo",
+            "Asia/Choibalsan" | "Asia/Ulan_Bator" => "Asia/Ulaanbaatar",
+            "Asia/Kashgar" => "Asia/Urumqi",
+            "Asia/Rangoon" => "Asia/Yangon",
+            "Atlantic/Faeroe" => "Atlantic/Faroe",
+            "Iceland" => "Atlantic/Reykjavik",
+            "Australia/South" => "Australia/Adelaide",
+            "Australia/Queensland" => "Australia/Brisbane",
+            "Australia/Yancowinna" => "Australia/Broken_Hill",
+            "Australia/North" => "Australia/Darwin",
+            "Australia/Currie" | "Australia/Tasmania" => "Australia/Hobart",
+            "Australia/LHI" => "Australia/Lord_Howe",
+            "Australia/Victoria" => "Australia/Melbourne",
+            "Australia/West" => "Australia/Perth",
+            "Australia/ACT" | "Australia/Canberra" | "Australia/NSW" => "Australia/Sydney",
+            "Europe/Tiraspol" => "Europe/Chisinau",
+            "Eire" => "Europe/Dublin",
+            "Asia/Istanbul" | "Turkey" => "Europe/Istanbul",
+            "Europe/Kiev" | "Europe/Uzhgorod" | "Europe/Zaporozhye" => "Europe/Kyiv",
+            "Portugal" => "Europe/Lisbon",
+            "Europe/Belfast" | "GB" | "GB-Eire" => "Europe/London",
+            "W-SU" => "Europe/Moscow",
+            "Poland" => "Europe/Warsaw",
+            "NZ" => "Pacific/Auckland",
+            "NZ-CHAT" => "Pacific/Chatham",
+            "Pacific/Truk" => "Pacific/Chuuk",
+            "Chile/EasterIsland" => "Pacific/Easter",
+            "US/Hawaii" => "Pacific/Honolulu",
+            "Pacific/Enderbury" => "Pacific/Kanton",
+            "Kwajalein" => "Pacific/Kwajalein",
+            "Pacific/Samoa" | "US/Samoa" => "Pacific/Pago_Pago",
+            "Pacific/Ponape" => "Pacific/Pohnpei",
+            _ => return None,
+        };
+
+        Some(canonical)
+    }
+
+    /// Get the country of [`timezone`](struct.IpData.html#structfield.timezone)
+    ///
+    /// Useful for cross-checking [`country_code`](struct.IpData.html#structfield.country_code).
+    /// Some zones are used by several countries (e.g. `Europe/Zurich` by Switzerland,
+    /// Germany and Liechtenstein), then the primary one is returned.
+    /// Returns `None` if the field is missing or the zone is unknown or not bound
+    /// to a country (e.g. `UTC`).
+    ///
+    /// # Notice
+    ///
+    /// The zones are looked up in built-in snapshots of `zone.tab` and `backward`
+    /// of the IANA time zone database, version 2025b (the one bundled with chrono-tz),
+    /// so aliases (e.g. `Europe/Kiev` or `Asia/Calcutta`) are resolved to their
+    /// canonical zones. Zones added in later versions return `None`
+    #[cfg(all(feature = "isocountry", feature = "chrono-tz"))]
+    pub fn timezone_country(&self) -> Option<isocountry::CountryCode> {
+        let timezone: chrono_tz::Tz = self.timezone.as_deref()?.parse().ok()?;
+        let name = timezone.name();
+        let country_code = Self::timezone_country_code(name)
+            .or_else(|| Self::timezone_country_code(Self::canonical_timezone(name)?))?;
+
+        isocountry::CountryCode::for_alpha2(country_code).ok()
+    }
+
+    fn require(value: &Option<String>, field: IpDataField) -> Result<&str, IpApiError> {
+        value
+            .as_deref()
+            .ok_or_else(|| IpApiError::MissingFields(vec![field]))
+    }
+
+    /// Get [`country`](struct.IpData.html#structfield.country)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_country(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.country, IpDataField::Country)
+    }
+
+    /// Get [`country_code`](struct.IpData.html#structfield.country_code)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_country_code(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.country_code, IpDataField::CountryCode)
+    }
+
+    /// Get [`region_name`](struct.IpData.html#structfield.region_name)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_region_name(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.region_name, IpDataField::RegionName)
+    }
+
+    /// Get [`city`](struct.IpData.html#structfield.city)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_city(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.city, IpDataField::City)
+    }
+
+    /// Get [`timezone`](struct.IpData.html#structfield.timezone)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_timezone(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.timezone, IpDataField::Timezone)
+    }
+
+    /// Get [`currency`](struct.IpData.html#structfield.currency)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_currency(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.currency, IpDataField::Currency)
+    }
+
+    /// Get [`isp`](struct.IpData.html#structfield.isp)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_isp(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.isp, IpDataField::Isp)
+    }
+
+    /// Get [`query`](struct.IpData.html#structfield.query)
+    /// or [`IpApiError::MissingFields`] if it is missing
+    pub fn require_query(&self) -> Result<&str, IpApiError> {
+        Self::require(&self.query, IpDataField::Query)
+    }
+
+    fn split_as_field(&self) -> Option<(u32, &str)> {
+        let as_field = self.as_field.as_deref()?.trim();
+        let (number, organization) = as_field.split_once(' ').unwrap_or((as_field, ""));
+        let number = number.strip_prefix("AS")?.parse().ok()?;
+
+        Some((number, organization.trim()))
+    }
+
+    /// Get AS number from [`as_field`](struct.IpData.html#structfield.as_field)
+    ///
+    /// Returns `None` if it's missing or doesn't start with `AS<number>`
+    ///
+    /// # Example
+    ///
+    /// 13335 (for `AS13335 Cloudflare, Inc.`)
+    pub fn asn(&self) -> Option<u32> {
+        self.split_as_field().map(|(number, _)| number)
+    }
+
+    /// Get AS organization from [`as_field`](struct.IpData.html#structfield.as_field)
+    ///
+    /// Returns `None` if it's missing or doesn't start with `AS<number>`
+    ///
+    /// # Example
+    ///
+    /// Cloudflare, Inc. (for `AS13335 Cloudflare, Inc.`)
+    pub fn as_org(&self) -> Option<&str> {
+        self.split_as_field()
+            .map(|(_, organization)| organization)
+            .filter(|organization| !organization.is_empty())
+    }
+
+    /// Combine the security flags into a score from 0 to 100 with the default [`RiskWeights`]
+    ///
+    /// See [`risk_score_with`](Self::risk_score_with)
+    pub fn risk_score(&self) -> u8 {
+        self.risk_score_with(RiskWeights::default())
+    }
+
+    /// Combine the security flags into a score from 0 to 100 with custom `weights`
+    ///
+    /// Weights of [`proxy`](struct.IpData.html#structfield.proxy),
+    /// [`hosting`](struct.IpData.html#structfield.hosting) and
+    /// [`mobile`](struct.IpData.html#structfield.mobile) that are `true` are summed up,
+    /// missing flags are treated as `false`.
+    ///
+    /// # Notice
+    ///
+    /// This is a heuristic that only surfaces the flags of the API, not a validated
+    /// fraud model, so calibrate the weights and thresholds on your own data
+    pub fn risk_score_with(&self, weights: RiskWeights) -> u8 {
+        let score: i16 = [
+            (self.proxy, weights.proxy),
+            (self.hosting, weights.hosting),
+            (self.mobile, weights.mobile),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| *flag == Some(true))
+        .map(|(_, weight)| i16::from(weight))
+        .sum();
+
+        score.clamp(0, 100) as u8
+    }
+
+    /// Guess the type of the network operator
+    ///
+    /// This is a best-effort heuristic over keywords in
+    /// [`isp`](struct.IpData.html#structfield.isp),
+    /// [`org`](struct.IpData.html#structfield.org) and
+    /// [`asname`](struct.IpData.html#structfield.asname) (e.g. "University", "Institute"
+    /// or ".edu" lead to [`NetworkCategory::Education`]), falling back to
+    /// [`hosting`](struct.IpData.html#structfield.hosting) and
+    /// [`mobile`](struct.IpData.html#structfield.mobile) flags.
+    /// The names are usually in English regardless of the language, but the result
+    /// may still be wrong, so don't rely on it for anything critical
+    pub fn network_category(&self) -> NetworkCategory {
+        const CATEGORIES: [(NetworkCategory, &[&str]); 5] = [
+            (
+                NetworkCategory::Education,
+                &[
+                    ".edu",
+                    "universit",
+                    "college",
+                    "school",
+                    "academ",
+                    "institut",
+                    "research network",
+                ],
+            ),
+            (
+                NetworkCategory::Government,
+                &[
+                    ".gov",
+                    "government",
+                    "ministry",
+                    "federal",
+                    "department of",
+                    "municipal",
+                ],
+            ),
+            (
+                NetworkCategory::Cloud,
+                &[
+                    "amazon",
+                    "aws",
+                    "google",
+                    "cloudflare",
+                    "microsoft",
+                    "azure",
+                    "oracle",
+                    "alibaba",
+                    "tencent",
+                    "fastly",
+                    "akamai",
+                ],
+            ),
+            (
+                NetworkCategory::Hosting,
+                &[
+                    "hosting",
+                    "data center",
+                    "datacenter",
+                    "colocation",
+                    "server",
+                    "ovh",
+                    "hetzner",
+                    "digitalocean",
+                    "linode",
+                    "vultr",
+                ],
+            ),
+            (
+                NetworkCategory::Isp,
+                &[
+                    "telecom",
+                    "telekom",
+                    "communications",
+                    "broadband",
+                    "cable",
+                    "mobile",
+                    "wireless",
+                    "cellular",
+                    "internet service",
+                ],
+            ),
+        ];
+
+        let names = [&self.isp, &self.org, &self.asname]
+            .into_iter()
+            .flatten()
+            .map(|name| name.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        for (category, keywords) in CATEGORIES {
+            if keywords.iter().any(|keyword| names.contains(keyword)) {
+                return category;
+            }
+        }
+
+        match (self.hosting, self.mobile) {
+            (Some(true), _) => NetworkCategory::Hosting,
+            (_, Some(true)) => NetworkCategory::Isp,
+            _ => NetworkCategory::Unknown,
+        }
+    }
+
+    /// Get trimmed [`region`](struct.IpData.html#structfield.region) short code
+    ///
+    /// Returns `None` if the region is missing or empty
+    pub fn region_code(&self) -> Option<&str> {
+        let region = self.region.as_deref()?.trim();
+
+        if region.is_empty() {
+            return None;
+        }
+
+        Some(region)
+    }
+
+    /// Guess whether [`region`](struct.IpData.html#structfield.region)
+    /// is a FIPS or an ISO code by its pattern
+    ///
+    /// Returns `None` if the region is missing or doesn't look like any of them
+    pub fn region_code_kind(&self) -> Option<RegionCodeKind> {
+        let region = self.region_code()?;
+
+        if region.chars().all(|char| char.is_ascii_digit()) {
+            Some(RegionCodeKind::Fips)
+        } else if region.chars().all(|char| char.is_ascii_alphanumeric()) {
+            Some(RegionCodeKind::Iso)
+        } else {
+            None
+        }
+    }
+}
+
+/// Represents where the result of [`IpApiConfig::make_request_cached`] came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The result was taken from the cache
+    Hit,
+
+    /// The result was requested from the API (and added to the cache if it's enabled)
+    Miss,
+}
+
+/// Rate limit state reported by the API in the `X-Rl` and `X-Ttl` headers
+///
+/// Returned by [`IpApiConfig::make_batch_request_with_meta`].
+/// The fields are `None` if the headers are missing or malformed
+/// (e.g. the headers aren't sent by the pro endpoint)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Number of requests remaining in the current window
+    pub remaining: Option<u8>,
+
+    /// Remaining time before the window is reset
+    pub reset_in: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let parse = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|header| header.to_str().ok())
+                .and_then(|header| header.parse::<u8>().ok())
+        };
+
+        RateLimitInfo {
+            remaining: parse("X-Rl"),
+            reset_in: parse("X-Ttl").map(|ttl| Duration::from_secs(ttl.into())),
+        }
+    }
+}
+
+/// Represents how batch requests (e.g. [`IpApiConfig::make_batch_request`]) handle targets
+/// that are detected as private or reserved locally (without a request)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BatchInvalidPolicy {
+    /// Return [`IpApiError::PrivateRange`] or [`IpApiError::ReservedRange`]
+    /// for the first such target, nothing is sent
+    Error,
+
+    /// Remove such targets before sending
+    ///
+    /// The results are in the same order as the remaining targets,
+    /// so there are no results for the removed ones. The removed targets are exactly
+    /// the ones for which [`is_valid_query`] returns [`QueryKind::Private`]
+    /// or [`QueryKind::Reserved`], so the results can be matched to the input like this:
+    ///
+    /// ```rust
+    /// use ip_api_client::{is_valid_query, QueryKind};
+    ///
+    /// let targets = vec!["1.1.1.1", "192.168.1.1", "8.8.8.8"];
+    /// let remaining: Vec<(usize, &str)> = targets
+    ///     .iter()
+    ///     .copied()
+    ///     .enumerate()
+    ///     .filter(|(_, target)| {
+    ///         !matches!(is_valid_query(target), QueryKind::Private | QueryKind::Reserved)
+    ///     })
+    ///     .collect();
+    ///
+    /// // The `n`-th result belongs to `targets[remaining[n].0]`
+    /// assert_eq!(remaining, [(0, "1.1.1.1"), (2, "8.8.8.8")]);
+    /// ```
+    Skip,
+
+    /// Send all targets as is, so the API returns the error
+    #[default]
+    Passthrough,
+}
+
+/// Represents what kind of target a string is, detected locally (without a request)
+///
+/// Returned by [`is_valid_query`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryKind {
+    /// Public IPv4 address
+    Ipv4,
+
+    /// Public IPv6 address
+    Ipv6,
+
+    /// Well-formed domain name (it isn't checked that it can be resolved)
+    Domain,
+
+    /// IP address from a private range, the API responds with [`IpApiError::PrivateRange`]
+    Private,
+
+    /// IP address from a reserved range, the API responds with [`IpApiError::ReservedRange`]
+    Reserved,
+
+    /// Neither an IP address nor a well-formed domain name
+    Invalid,
+}
+
+/// Represents a rough type of the network operator
+///
+/// Returned by [`IpData::network_category`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetworkCategory {
+    /// Big cloud provider
+    ///
+    /// # Example
+    ///
+    /// Amazon **OR** Cloudflare **OR** Google
+    Cloud,
+
+    /// Internet service provider or mobile operator
+    Isp,
+
+    /// University, college or school
+    Education,
+
+    /// Government agency
+    Government,
+
+    /// Hosting provider or data center
+    Hosting,
+
+    /// None of the known keywords is found
+    Unknown,
+}
+
+/// Security-relevant information about the IP address
+///
+/// Returned by [`IpApiConfig::security_check`]
+#[derive(Clone, Debug)]
+pub struct SecurityReport {
+    /// IP/Domain used for the query
+    pub query: String,
+
+    /// Proxy, VPN or Tor exit address
+    pub is_proxy: bool,
+
+    /// Mobile (cellular) connection
+    pub is_mobile: bool,
+
+    /// Hosting, colocated or data center
+    pub is_hosting: bool,
+}
+
+/// Weights of the security flags used by [`IpData::risk_score_with`]
+///
+/// Each flag that is `true` adds its weight to the score, the sum is clamped to 0–100.
+/// The default weights are `proxy` = 50, `hosting` = 30 and `mobile` = -10
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RiskWeights {
+    /// Weight of [`proxy`](struct.IpData.html#structfield.proxy)
+    pub proxy: i8,
+
+    /// Weight of [`hosting`](struct.IpData.html#structfield.hosting)
+    pub hosting: i8,
+
+    /// Weight of [`mobile`](struct.IpData.html#structfield.mobile)
+    pub mobile: i8,
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        RiskWeights {
+            proxy: 50,
+            hosting: 30,
+            mobile: -10,
+        }
+    }
+}
+
+/// Network attribution of the IP address
+///
+/// Returned by [`IpApiConfig::network_of`]
+#[derive(Clone, Debug)]
+pub struct NetworkIdentity {
+    /// IP/Domain used for the query
+    pub query: String,
+
+    /// Internet Service Provider name
+    pub isp: Option<String>,
+
+    /// AS number parsed from [`as_field`](struct.IpData.html#structfield.as_field)
+    ///
+    /// # Example
+    ///
+    /// 13335
+    pub asn: Option<u32>,
+
+    /// AS organization parsed from [`as_field`](struct.IpData.html#structfield.as_field)
+    ///
+    /// # Example
+    ///
+    /// Cloudflare, Inc.
+    pub as_org: Option<String>,
+
+    /// Organization name
+    pub org: Option<String>,
+}
+
+/// The request that would be sent to [ip-api.com API](https://ip-api.com/docs/api:json)
+///
+/// Returned by [`IpApiConfig::dry_run`]
+#[derive(Clone, Debug)]
+pub struct PreparedRequest {
+    /// HTTP method
+    pub method: Method,
+
+    /// Full request URL
+    pub url: String,
+
+    /// Request headers
+    pub headers: HeaderMap,
+
+    /// Request body (`None` if the request has no body)
+    pub body: Option<String>,
+}
+
+impl PreparedRequest {
+    fn into_request(self) -> Result<Request<Body>, IpApiError> {
+        let mut builder = Request::builder().method(self.method).uri(self.url);
+
+        if let Some(headers) = builder.headers_mut() {
+            *headers = self.headers;
+        }
+
+        let Ok(request) = builder.body(self.body.map(Body::from).unwrap_or_else(Body::empty))
+        else {
+            return Err(IpApiError::UnexpectedError(Some(
+                "Failed to build a request".into(),
+            )));
+        };
+
+        Ok(request)
+    }
+}
+
+/// Incrementally parsed results of a batch request
+///
+/// Returned by [`IpApiConfig::make_batch_stream`].
+/// Implements [`Stream`](futures_core::Stream), the results can be taken
+/// with [`next`](Self::next) as well
+#[cfg(feature = "stream")]
+pub struct BatchStream {
+    reader: Option<BatchReader>,
+    pending: Option<BatchReaderFuture>,
+}
+
+#[cfg(feature = "stream")]
+type BatchReaderFuture =
+    Pin<Box<dyn Future<Output = (BatchReader, Option<Result<IpData, IpApiError>>)> + Send>>;
+
+#[cfg(feature = "stream")]
+impl BatchStream {
+    fn new(config: IpApiConfig, requests: VecDeque<(String, Vec<String>)>) -> Self {
+        BatchStream {
+            reader: Some(BatchReader::new(config, requests)),
+            pending: None,
+        }
+    }
+
+    /// Get the next result, or `None` if all targets are processed
+    ///
+    /// Errors related to a target (e.g. [`IpApiError::InvalidQuery`]) are yielded
+    /// in place of its result, and the stream goes on.
+    /// After any other error (e.g. [`IpApiError::RateLimit`], a response that isn't
+    /// an array, or a number of results that doesn't match the number of targets)
+    /// the stream ends
+    pub async fn next(&mut self) -> Option<Result<IpData, IpApiError>> {
+        std::future::poll_fn(|cx| futures_core::Stream::poll_next(Pin::new(&mut *self), cx)).await
+    }
+}
+
+#[cfg(feature = "stream")]
+impl futures_core::Stream for BatchStream {
+    type Item = Result<IpData, IpApiError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = self.get_mut();
+
+        if stream.pending.is_none() {
+            let Some(mut reader) = stream.reader.take() else {
+                return Poll::Ready(None);
+            };
+
+            stream.pending = Some(Box::pin(async move {
+                let result = reader.next().await;
+
+                (reader, result)
+            }));
+        }
+
+        let Some(pending) = &mut stream.pending else {
+            return Poll::Ready(None);
+        };
+        let Poll::Ready((reader, result)) = pending.as_mut().poll(cx) else {
+            return Poll::Pending;
+        };
+
+        stream.pending = None;
+        stream.reader = Some(reader);
+
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(feature = "stream")]
+impl fmt::Debug for BatchStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchStream")
+            .field("reader", &self.reader)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Position in the top-level array of a batch response
+#[cfg(feature = "stream")]
+#[derive(Clone, Copy, Debug)]
+enum BatchArrayState {
+    /// Before `[`
+    Start,
+
+    /// Right after `[`, so either an element or `]` is next
+    First,
+
+    /// After `,`, so an element is next
+    Element,
+
+    /// After an element, so either `,` or `]` is next
+    Separator,
+
+    /// After `]`
+    Complete,
+
+    /// The body isn't an array, it's parsed once the body is received
+    NotArray,
+}
+
+#[cfg(feature = "stream")]
+#[derive(Debug)]
+struct BatchReader {
+    config: IpApiConfig,
+    requests: VecDeque<(String, Vec<String>)>,
+    targets: VecDeque<String>,
+    response: Option<Body>,
+    buffer: Vec<u8>,
+    position: usize,
+    received: usize,
+    array: BatchArrayState,
+}
+
+#[cfg(feature = "stream")]
+impl BatchReader {
+    fn new(config: IpApiConfig, requests: VecDeque<(String, Vec<String>)>) -> Self {
+        BatchReader {
+            config,
+            requests,
+            targets: VecDeque::new(),
+            response: None,
+            buffer: Vec::new(),
+            position: 0,
+            received: 0,
+            array: BatchArrayState::Start,
+        }
+    }
+
+    fn parse_error() -> IpApiError {
+        IpApiError::UnexpectedError(Some("Failed to parse body from the response".into()))
+    }
+
+    /// Get the JSON of the next element of the top-level array
+    fn take_element(&mut self) -> Option<Result<Vec<u8>, IpApiError>> {
+        loop {
+            self.position += self.buffer[self.position..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_whitespace())
+                .count();
+
+            let byte = *self.buffer.get(self.position)?;
+
+            self.array = match (self.array, byte) {
+                (BatchArrayState::Start, b'[') => BatchArrayState::First,
+                // Most likely an error object
+                (BatchArrayState::Start, _) => BatchArrayState::NotArray,
+                (BatchArrayState::NotArray, _) => return None,
+                (BatchArrayState::First | BatchArrayState::Separator, b']') => {
+                    BatchArrayState::Complete
+                }
+                (BatchArrayState::Separator, b',') => BatchArrayState::Element,
+                (BatchArrayState::First | BatchArrayState::Element, _) => {
+                    return self.take_value();
+                }
+                (BatchArrayState::Separator | BatchArrayState::Complete, _) => {
+                    return Some(Err(Self::parse_error()));
+                }
+            };
+
+            if let BatchArrayState::NotArray = self.array {
+                return None;
+            }
+
+            self.position += 1;
+        }
+    }
+
+    /// Take the value at the position if it's received completely
+    fn take_value(&mut self) -> Option<Result<Vec<u8>, IpApiError>> {
+        let rest = &self.buffer[self.position..];
+        let mut values = serde_json::Deserializer::from_slice(rest).into_iter::<de::IgnoredAny>();
+
+        match values.next()? {
+            // A number may continue in the next chunk
+            Ok(_) if values.byte_offset() == rest.len() => None,
+            Ok(_) => {
+                let end = values.byte_offset();
+                let element = rest[..end].to_vec();
+
+                self.buffer.drain(..self.position + end);
+                self.position = 0;
+                self.array = BatchArrayState::Separator;
+
+                Some(Ok(element))
+            }
+            Err(error) if error.is_eof() => None,
+            Err(_) => Some(Err(Self::parse_error())),
+        }
+    }
+
+    fn parse_element(&mut self, element: &[u8]) -> Result<IpData, IpApiError> {
+        let target = self.targets.pop_front().unwrap_or_default();
+
+        let Ok(element) = std::str::from_utf8(element) else {
+            return Err(IpApiError::UnexpectedError(Some(
+                "Failed to convert body from the response to String".into(),
+            )));
+        };
+
+        let Some(ip_data) =
+            IpApiConfig::parse_ip_data(RequestMode::Single, &[&target], element)?.pop()
+        else {
+            return Err(IpApiError::EmptyResponse);
+        };
+
+        self.config.check_missing_fields(&ip_data)?;
+
+        Ok(ip_data)
+    }
+
+    /// Get the next result from the received part of the response
+    fn take_result(&mut self) -> Option<Result<IpData, IpApiError>> {
+        match self.take_element()? {
+            Ok(_) if self.targets.is_empty() => Some(self.fail(IpApiError::UnexpectedError(Some(
+                "Received more results than targets".into(),
+            )))),
+            Ok(element) => Some(self.parse_element(&element)),
+            Err(error) => Some(self.fail(error)),
+        }
+    }
+
+    fn reset_response(&mut self) {
+        self.response = None;
+        self.targets.clear();
+        self.buffer.clear();
+        self.position = 0;
+        self.received = 0;
+        self.array = BatchArrayState::Start;
+    }
+
+    /// End the stream with `error`
+    fn fail(&mut self, error: IpApiError) -> Result<IpData, IpApiError> {
+        self.requests.clear();
+        self.reset_response();
+
+        let result = Err(error);
+
+        #[cfg(feature = "metrics")]
+        IpApiConfig::record_metrics(&result);
+
+        result
+    }
+
+    fn finish_response(&mut self) -> Result<(), IpApiError> {
+        let result = match self.array {
+            BatchArrayState::NotArray => {
+                let body = String::from_utf8_lossy(&self.buffer);
+
+                IpApiConfig::parse_json::<IpApiMessage>(&body)
+                    .and_then(|message| IpApiConfig::check_error_message(message, ""))
+                    .and(Err(Self::parse_error()))
+            }
+            BatchArrayState::Start => Err(IpApiError::EmptyResponse),
+            BatchArrayState::Complete if !self.targets.is_empty() => Err(
+                IpApiError::UnexpectedError(Some("Received fewer results than targets".into())),
+            ),
+            BatchArrayState::Complete => Ok(()),
+            _ => Err(Self::parse_error()),
+        };
+
+        self.reset_response();
+
+        result
+    }
+
+    async fn next(&mut self) -> Option<Result<IpData, IpApiError>> {
+        loop {
+            if let Some(result) = self.take_result() {
+                return Some(result);
+            }
+
+            let Some(response) = &mut self.response else {
+                let (body, targets) = self.requests.pop_front()?;
+
+                match self.config.send_batch_stream_request(body).await {
+                    Ok(response) => {
+                        self.response = Some(response);
+                        self.targets = targets.into();
+                    }
+                    Err(error) => return Some(self.fail(error)),
+                }
+
+                continue;
+            };
+
+            let chunk = response.data();
+            let chunk = match self.config.read_timeout {
+                Some(read_timeout) => tokio::time::timeout(read_timeout, chunk)
+                    .await
+                    .map_err(|_| IpApiError::Timeout),
+                None => Ok(chunk.await),
+            };
+
+            let result = match chunk {
+                Ok(Some(Ok(chunk)))
+                    if self.received + chunk.len() <= self.config.max_response_bytes =>
+                {
+                    self.received += chunk.len();
+                    self.buffer.extend_from_slice(&chunk);
+                    continue;
+                }
+                Ok(Some(Ok(_))) => Err(IpApiError::ResponseTooLarge),
+                Ok(Some(Err(_))) => Err(IpApiError::ConnectionLost),
+                Ok(None) => self.finish_response().map(|()| {
+                    #[cfg(feature = "metrics")]
+                    IpApiConfig::record_metrics(&Ok::<(), IpApiError>(()));
+                }),
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = result {
+                return Some(self.fail(error));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json-logs")]
+#[derive(Clone)]
+struct LogSink(Arc<Mutex<dyn io::Write + Send>>);
+
+#[cfg(feature = "json-logs")]
+impl fmt::Debug for LogSink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("LogSink")
+    }
+}
+
+#[derive(Debug)]
+struct Ipv6Unavailable;
+
+impl Display for Ipv6Unavailable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "no IPv6 address found")
+    }
+}
+
+impl Error for Ipv6Unavailable {}
+
+#[derive(Clone, Debug)]
+struct Resolver {
+    gai: GaiResolver,
+    is_ipv4_only: bool,
+    is_ipv6_only: bool,
+}
+
+impl Service<Name> for Resolver {
+    type Response = vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.gai.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolving = self.gai.call(name);
+        let is_ipv4_only = self.is_ipv4_only;
+        let is_ipv6_only = self.is_ipv6_only;
+
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = resolving
+                .await?
+                .filter(|address| !is_ipv4_only || address.is_ipv4())
+                .filter(|address| !is_ipv6_only || address.is_ipv6())
+                .collect();
+
+            if addresses.is_empty() && is_ipv4_only {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrNotAvailable,
+                    "no IPv4 address found",
+                ));
+            }
+
+            if addresses.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrNotAvailable,
+                    Ipv6Unavailable,
+                ));
+            }
+
+            Ok(addresses.into_iter())
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EnvProxy {
+    http: Option<Uri>,
+    https: Option<Uri>,
+    no_proxy: Vec<String>,
+}
+
+impl EnvProxy {
+    fn from_env() -> Self {
+        let read = |names: [&str; 2]| {
+            names
+                .iter()
+                .find_map(|name| env::var(name).ok())
+                .filter(|value| !value.trim().is_empty())
+        };
+        let parse = |value: String| {
+            let value = value.trim();
+
+            if value.contains("://") {
+                value.parse().ok()
+            } else {
+                format!("http://{value}").parse().ok()
+            }
+        };
+
+        EnvProxy {
+            http: read(["http_proxy", "HTTP_PROXY"]).and_then(parse),
+            https: read(["https_proxy", "HTTPS_PROXY"]).and_then(parse),
+            no_proxy: read(["no_proxy", "NO_PROXY"])
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|host| host.trim().trim_start_matches('.').to_ascii_lowercase())
+                        .filter(|host| !host.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn proxy_for(&self, destination: &Uri) -> Option<&Uri> {
+        let host = destination.host()?.to_ascii_lowercase();
+        let is_excluded = self.no_proxy.iter().any(|pattern| {
+            pattern == "*" || host == *pattern || host.ends_with(&format!(".{pattern}"))
+        });
+
+        if is_excluded {
+            return None;
+        }
+
+        match destination.scheme_str() {
+            Some("https") => self.https.as_ref(),
+            _ => self.http.as_ref(),
+        }
+    }
+}
+
+struct ConnectorStream {
+    inner: TcpStream,
+    is_proxied: bool,
+}
+
+impl Connection for ConnectorStream {
+    fn connected(&self) -> Connected {
+        self.inner.connected().proxy(self.is_proxied)
+    }
+}
+
+impl AsyncRead for ConnectorStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ConnectorStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Connector {
+    http: HttpConnector<Resolver>,
+    host_ip: Option<IpAddr>,
+    host_port: Option<u16>,
+    proxy: Option<EnvProxy>,
+}
+
+impl Connector {
+    fn new(is_ipv4_only: bool, is_ipv6_only: bool) -> Self {
+        Connector {
+            http: HttpConnector::new_with_resolver(Resolver {
+                gai: GaiResolver::new(),
+                is_ipv4_only,
+                is_ipv6_only,
+            }),
+            host_ip: None,
+            host_port: None,
+            proxy: None,
+        }
+    }
+
+    fn override_destination(&self, destination: Uri) -> Uri {
+        let Some(host_ip) = self.host_ip else {
+            return destination;
+        };
+
+        // Other hosts (e.g. the fallback) are connected to as usual
+        if destination.host() != Some(API_HOST) {
+            return destination;
+        }
+        let port = self.host_port.or(destination.port_u16()).unwrap_or(80);
+        let address = SocketAddr::new(host_ip, port);
+
+        let mut parts = destination.clone().into_parts();
+        let Ok(authority) = address.to_string().parse() else {
+            return destination;
+        };
+        parts.authority = Some(authority);
+
+        Uri::from_parts(parts).unwrap_or(destination)
+    }
+}
+
+impl Service<Uri> for Connector {
+    type Response = ConnectorStream;
+    type Error = Box<dyn Error + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, destination: Uri) -> Self::Future {
+        let proxy = self
+            .proxy
+            .as_ref()
+            .and_then(|proxy| proxy.proxy_for(&destination))
+            .cloned();
+        let is_proxied = proxy.is_some();
+        let destination = match proxy {
+            Some(proxy) => proxy,
+            None => self.override_destination(destination),
+        };
+        let connecting = self.http.call(destination);
+
+        Box::pin(async move {
+            Ok(ConnectorStream {
+                inner: connecting.await?,
+                is_proxied,
+            })
+        })
+    }
+}
+
+#[derive(Debug)]
+struct ResponseCache {
+    max_size: usize,
+    entries: HashMap<String, IpData>,
+    order: VecDeque<String>,
+}
+
+impl ResponseCache {
+    fn new(max_size: usize) -> Self {
+        ResponseCache {
+            max_size,
+            entries: HashMap::with_capacity(max_size),
+            order: VecDeque::with_capacity(max_size),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<IpData> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: String, ip_data: IpData) {
+        if self.max_size == 0 {
+            return;
+        }
+
+        if self.entries.insert(key.clone(), ip_data).is_some() {
+            return;
+        }
+
+        self.order.push_back(key);
+
+        while self.order.len() > self.max_size {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum RequestMode {
+    Single,
+    Batch,
+}
+
+impl RequestMode {
+    fn resource(&self) -> &'static str {
+        match self {
+            RequestMode::Single => "json",
+            RequestMode::Batch => "batch",
+        }
+    }
+
+    fn method(&self) -> Method {
+        match self {
+            RequestMode::Single => Method::GET,
+            RequestMode::Batch => Method::POST,
+        }
+    }
+
+    fn of(uri: &Uri) -> Self {
+        if uri.path().starts_with("/batch") {
+            return RequestMode::Batch;
+        }
+
+        RequestMode::Single
+    }
+
+    fn pacing_limit(&self) -> usize {
+        match self {
+            RequestMode::Single => PACING_LIMIT,
+            RequestMode::Batch => BATCH_PACING_LIMIT,
+        }
+    }
+}
+
+/// Represents how long to wait before retrying a failed request,
+/// see [`IpApiConfig::set_backoff`]
+///
+/// [`Backoff::default`] is `Fixed(200ms)`, so an unavailable host isn't retried
+/// in a tight loop
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backoff {
+    /// Retry failed requests immediately,
+    /// and requests rejected by the rate limit exactly after `X-Ttl`
+    Immediate,
+
+    /// Wait the same duration before every retry of a failed request,
+    /// requests rejected by the rate limit are retried exactly after `X-Ttl`
+    Fixed(Duration),
+
+    /// Exponential backoff with "full jitter"
+    ///
+    /// Before the `n`-th retry of a failed request, wait a random duration
+    /// between zero and `min(max, base * 2^(n - 1))`.
+    /// Requests rejected by the rate limit still wait for `X-Ttl`,
+    /// plus a random duration between zero and `min(max, base)`
+    ExponentialJitter {
+        /// Upper bound of the wait before the first retry
+        base: Duration,
+
+        /// Upper bound of the wait before any retry
+        max: Duration,
+    },
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::Fixed(DEFAULT_RETRY_WAIT)
+    }
+}
+
+impl Backoff {
+    fn random_up_to(limit: Duration) -> Duration {
+        // Every `RandomState` is seeded differently, which is enough for jitter
+        let random = RandomState::new().build_hasher().finish();
+
+        limit.mul_f64(random as f64 / u64::MAX as f64)
+    }
+
+    fn retry_wait(&self, retry: u32) -> Duration {
+        let (base, max) = match *self {
+            Backoff::Immediate => return Duration::ZERO,
+            Backoff::Fixed(wait) => return wait,
+            Backoff::ExponentialJitter { base, max } => (base, max),
+        };
+        let factor = 1_u32
+            .checked_shl(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let limit = base.checked_mul(factor).unwrap_or(max).min(max);
+
+        Self::random_up_to(limit)
+    }
+
+    fn rate_limit_jitter(&self) -> Duration {
+        let Backoff::ExponentialJitter { base, max } = *self else {
+            return Duration::ZERO;
+        };
+
+        Self::random_up_to(base.min(max))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RetryBudget {
+    max_attempts: u32,
+    max_total_wait: Duration,
+}
+
+#[derive(Debug)]
+struct Pacing {
+    window: Duration,
+    single: Mutex<VecDeque<Instant>>,
+    batch: Mutex<VecDeque<Instant>>,
+}
+
+impl Pacing {
+    fn new(window: Duration) -> Self {
+        Pacing {
+            window,
+            single: Mutex::new(VecDeque::with_capacity(PACING_LIMIT)),
+            batch: Mutex::new(VecDeque::with_capacity(BATCH_PACING_LIMIT)),
+        }
+    }
+
+    /// Requests to `/json` and `/batch` are limited separately
+    fn timestamps(&self, mode: RequestMode) -> &Mutex<VecDeque<Instant>> {
+        match mode {
+            RequestMode::Single => &self.single,
+            RequestMode::Batch => &self.batch,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RequestQuota {
+    max_requests: u32,
+    used: Arc<Mutex<u32>>,
+}
+
+/// Configuration structure allows you to customize the requested fields in the request
+/// to save traffic
+#[derive(Clone, Debug)]
+pub struct IpApiConfig {
+    numeric_field: u32,
+    is_continent_included: bool,
+    is_continent_code_included: bool,
+    is_country_included: bool,
+    is_country_code_included: bool,
+    is_region_included: bool,
+    is_region_name_included: bool,
+    is_city_included: bool,
+    is_district_included: bool,
+    is_zip_included: bool,
+    is_lat_included: bool,
+    is_lon_included: bool,
+    is_timezone_included: bool,
+    is_offset_included: bool,
+    is_currency_included: bool,
+    is_isp_included: bool,
+    is_org_included: bool,
+    is_as_field_included: bool,
+    is_asname_included: bool,
+    is_reverse_included: bool,
+    is_mobile_included: bool,
+    is_proxy_included: bool,
+    is_hosting_included: bool,
+    is_query_included: bool,
+    language: IpApiLanguage,
+    is_strict: bool,
+    retry_budget: Option<RetryBudget>,
+    backoff: Backoff,
+    batch_invalid_policy: BatchInvalidPolicy,
+    fallback_base_url: Option<String>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    max_response_bytes: usize,
+    cache: Option<Arc<Mutex<ResponseCache>>>,
+    pacing: Option<Arc<Pacing>>,
+    quota: Option<RequestQuota>,
+    #[cfg(feature = "json-logs")]
+    log_sink: Option<LogSink>,
+    host_ip: Option<IpAddr>,
+    host_port: Option<u16>,
+    local_ip: Option<IpAddr>,
+    proxy: Option<EnvProxy>,
+    is_ipv4_only: bool,
+    is_ipv6_only: bool,
+    client: Client<Connector>,
+}
+
+impl IpApiConfig {
+    fn build_uri(
+        mode: RequestMode,
+        target: Option<&str>,
+        fields: &str,
+        language: IpApiLanguage,
+    ) -> String {
+        format!(
+            "{}/{}/{}?fields={}{}",
+            API_BASE_URL,
+            mode.resource(),
+            target.map(Self::normalize_target).unwrap_or_default(),
             fields,
             match language {
-                IpApiLanguage::De => "&lang=de",
-                IpApiLanguage::En => "",
-                IpApiLanguage::Es => "&lang=es",
-                IpApiLanguage::Fr => "&lang=fr",
-                IpApiLanguage::Ja => "&lang=ja",
-                IpApiLanguage::PtBr => "&lang=pt-BR",
-                IpApiLanguage::Ru => "&lang=ru",
-                IpApiLanguage::ZhCn => "&lang=zh-CN",
+                IpApiLanguage::En => String::new(),
+                language => format!("&lang={}", language.code()),
+            }
+        )
+    }
+
+    /// Domains are case-insensitive and may end with a dot, IPs are left untouched
+    fn normalize_target(target: &str) -> String {
+        if target.parse::<IpAddr>().is_ok() {
+            return target.into();
+        }
+
+        target.strip_suffix('.').unwrap_or(target).to_lowercase()
+    }
+
+    fn build_batch_body(entries: Vec<BatchEntry>) -> String {
+        json!(entries).to_string()
+    }
+
+    fn build_client(&self) -> Client<Connector> {
+        let mut connector = Connector::new(self.is_ipv4_only, self.is_ipv6_only);
+        connector.http.set_connect_timeout(self.connect_timeout);
+        connector.http.set_local_address(self.local_ip);
+        connector.host_ip = self.host_ip;
+        connector.host_port = self.host_port;
+        connector.proxy = self.proxy.clone();
+
+        Client::builder().build(connector)
+    }
+
+    fn missing_fields(&self, ip_data: &IpData) -> Vec<IpDataField> {
+        Self::missing_fields_in(self.numeric_field, ip_data)
+    }
+
+    fn missing_fields_in(numeric_field: u32, ip_data: &IpData) -> Vec<IpDataField> {
+        ip_data
+            .fields()
+            .into_iter()
+            .filter(|(field, value)| value.is_none() && numeric_field & field.bit() != 0)
+            .map(|(field, _)| field)
+            .collect()
+    }
+
+    async fn wait_for_pacing(&self, mode: RequestMode) {
+        let Some(pacing) = &self.pacing else {
+            return;
+        };
+
+        loop {
+            let wait = {
+                let mut timestamps = pacing
+                    .timestamps(mode)
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner());
+                let now = Instant::now();
+
+                while timestamps
+                    .front()
+                    .is_some_and(|timestamp| now.duration_since(*timestamp) >= pacing.window)
+                {
+                    timestamps.pop_front();
+                }
+
+                if timestamps.len() < mode.pacing_limit() {
+                    timestamps.push_back(now);
+                    return;
+                }
+
+                pacing.window - now.duration_since(timestamps[0])
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    #[cfg(feature = "json-logs")]
+    fn log_request(
+        &self,
+        uri: &Uri,
+        latency: Duration,
+        result: &Result<Response<Body>, hyper::Error>,
+    ) {
+        let Some(LogSink(sink)) = &self.log_sink else {
+            return;
+        };
+
+        let target = uri.path().strip_prefix("/json/");
+        let fields = uri
+            .query()
+            .and_then(|query| {
+                query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("fields="))
+            })
+            .and_then(|fields| fields.parse::<u32>().ok())
+            .map(|fields| (fields & !IpDataField::Message.bit()).count_ones());
+        let status = result
+            .as_ref()
+            .ok()
+            .map(|response| response.status().as_u16());
+        let outcome = match status {
+            Some(429) => "rate_limited",
+            Some(status) if status < 400 => "ok",
+            Some(_) => "http_error",
+            None => "connection_error",
+        };
+
+        let entry = json!({
+            "target": target,
+            "fields": fields,
+            "status": status,
+            "latency_ms": latency.as_millis() as u64,
+            "outcome": outcome,
+        });
+
+        let mut sink = sink.lock().unwrap_or_else(|error| error.into_inner());
+        // Logging must never fail the request
+        let _ = writeln!(sink, "{entry}");
+    }
+
+    fn take_quota(&self) -> Result<(), IpApiError> {
+        let Some(quota) = &self.quota else {
+            return Ok(());
+        };
+
+        let mut used = quota.used.lock().unwrap_or_else(|error| error.into_inner());
+
+        if *used >= quota.max_requests {
+            return Err(IpApiError::QuotaExceeded);
+        }
+
+        *used += 1;
+
+        Ok(())
+    }
+
+    async fn send(&self, request: Request<Body>) -> Result<Response<Body>, IpApiError> {
+        self.take_quota()?;
+        self.wait_for_pacing(RequestMode::of(request.uri())).await;
+
+        #[cfg(feature = "json-logs")]
+        let (uri, started_at) = (request.uri().clone(), Instant::now());
+
+        let result = self.client.request(request).await;
+
+        #[cfg(feature = "json-logs")]
+        self.log_request(&uri, started_at.elapsed(), &result);
+
+        let error = match result {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+
+        let mut is_io_error = false;
+        let mut source: Option<&(dyn Error + 'static)> = Some(&error);
+        while let Some(error) = source {
+            let is_ipv6_unavailable = error
+                .downcast_ref::<io::Error>()
+                .and_then(|error| error.get_ref())
+                .is_some_and(|error| error.is::<Ipv6Unavailable>());
+
+            if is_ipv6_unavailable {
+                return Err(IpApiError::Ipv6Unavailable);
+            }
+
+            // Errors of the resolver have the same kinds, but they aren't OS errors
+            let is_bind_error = error.downcast_ref::<io::Error>().is_some_and(|error| {
+                error.raw_os_error().is_some()
+                    && matches!(
+                        error.kind(),
+                        io::ErrorKind::AddrNotAvailable | io::ErrorKind::AddrInUse
+                    )
+            });
+
+            if self.local_ip.is_some() && is_bind_error {
+                return Err(IpApiError::BindFailed);
+            }
+
+            is_io_error |= error.is::<io::Error>();
+            source = error.source();
+        }
+
+        if error.is_connect() {
+            return Err(IpApiError::ConnectionFailed);
+        }
+
+        if is_io_error || error.is_incomplete_message() || error.is_closed() {
+            return Err(IpApiError::ConnectionLost);
+        }
+
+        Err(IpApiError::UnexpectedError(Some(
+            "Failed to make a request".into(),
+        )))
+    }
+
+    async fn send_with_fallback(
+        &self,
+        request: &PreparedRequest,
+    ) -> Result<Response<Body>, IpApiError> {
+        let result = self.send(request.clone().into_request()?).await;
+
+        let is_failed = match &result {
+            Ok(response) => response.status().is_server_error(),
+            Err(error) => matches!(
+                error,
+                IpApiError::ConnectionFailed | IpApiError::ConnectionLost
+            ),
+        };
+
+        if !is_failed {
+            return result;
+        }
+
+        let Some(fallback_base_url) = &self.fallback_base_url else {
+            return result;
+        };
+        let Some(path) = request.url.strip_prefix(API_BASE_URL) else {
+            return result;
+        };
+
+        let request = PreparedRequest {
+            url: format!("{fallback_base_url}{path}"),
+            ..request.clone()
+        };
+
+        self.send(request.into_request()?).await
+    }
+
+    /// Send `request` and `read` the response, retrying the rate limit, 5xx responses
+    /// and [transient errors](IpApiError::is_transient) (of both steps) within the budget
+    async fn send_with_retries<T, F>(
+        &self,
+        request: PreparedRequest,
+        read: impl Fn(Response<Body>) -> F,
+    ) -> Result<T, IpApiError>
+    where
+        F: Future<Output = Result<T, IpApiError>>,
+    {
+        let mut attempts = 0;
+        let mut total_wait = Duration::ZERO;
+
+        loop {
+            attempts += 1;
+
+            let (result, wait) = match self.send_with_fallback(&request).await {
+                Ok(response) => match Self::check_response(&response) {
+                    Err(IpApiError::RateLimit(ttl)) => (
+                        Err(IpApiError::RateLimit(ttl)),
+                        Duration::from_secs(ttl.into()) + self.backoff.rate_limit_jitter(),
+                    ),
+                    Err(error) => return Err(error),
+                    Ok(()) => {
+                        let is_server_error = response.status().is_server_error();
+                        let result = read(response).await;
+                        let is_transient = result.as_ref().is_err_and(IpApiError::is_transient);
+
+                        if !is_server_error && !is_transient {
+                            return result;
+                        }
+
+                        (result, self.backoff.retry_wait(attempts))
+                    }
+                },
+                Err(error) if error.is_transient() => {
+                    (Err(error), self.backoff.retry_wait(attempts))
+                }
+                Err(error) => return Err(error),
+            };
+
+            let Some(retry_budget) = &self.retry_budget else {
+                return result;
+            };
+
+            if attempts >= retry_budget.max_attempts
+                || total_wait + wait > retry_budget.max_total_wait
+            {
+                return result;
             }
-        )
+
+            total_wait += wait;
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn check_missing_fields(&self, ip_data: &IpData) -> Result<(), IpApiError> {
+        if !self.is_strict {
+            return Ok(());
+        }
+
+        let missing_fields = self.missing_fields(ip_data);
+
+        if !missing_fields.is_empty() {
+            return Err(IpApiError::MissingFields(missing_fields));
+        }
+
+        Ok(())
+    }
+
+    fn fields_names(&self) -> String {
+        self.included_fields()
+            .iter()
+            .map(|field| field.name())
+            .collect::<Vec<_>>()
+            .join(",")
     }
 
     fn check_response(response: &Response<Body>) -> Result<(), IpApiError> {
@@ -354,122 +5179,1093 @@ impl IpApiConfig {
         Ok(())
     }
 
-    fn check_error_message(message: Option<String>) -> Result<(), IpApiError> {
-        if let Some(message) = message {
-            return match message.as_str() {
+    /// At least two dot-separated labels of letters, digits and hyphens
+    /// (not at the start or end of a label), a trailing dot is allowed.
+    /// Mistyped IPv4 addresses (e.g. `1.1.1.one`) don't count
+    fn is_domain(target: &str) -> bool {
+        let domain = target.strip_suffix('.').unwrap_or(target);
+        let labels: Vec<&str> = domain.split('.').collect();
+
+        let is_label = |label: &&str| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '-')
+        };
+        let is_numeric = |label: &&str| label.chars().all(|char| char.is_ascii_digit());
+
+        // Four labels with the first three numeric is a mistyped IPv4 address
+        let is_ipv4_like = labels.len() == 4 && labels[..3].iter().all(is_numeric);
+
+        labels.len() >= 2
+            && domain.len() <= 253
+            && labels.iter().all(is_label)
+            && !is_numeric(&labels[labels.len() - 1])
+            && !is_ipv4_like
+    }
+
+    /// The type of an invalid query is decided by `target` (as passed by the caller),
+    /// not by the query echoed in the response
+    fn check_error_message(message: IpApiMessage, target: &str) -> Result<(), IpApiError> {
+        if let Some(error) = message.message {
+            return match error.as_str() {
+                "invalid query" if Self::is_domain(target) => {
+                    Err(IpApiError::DomainNotResolved(target.into()))
+                }
                 "invalid query" => Err(IpApiError::InvalidQuery),
                 "private range" => Err(IpApiError::PrivateRange),
                 "reserved range" => Err(IpApiError::ReservedRange),
-                message => Err(IpApiError::UnexpectedError(Some(message.into()))),
+                error => Err(IpApiError::UnexpectedError(Some(error.into()))),
             };
         }
 
         Ok(())
     }
 
-    async fn parse_response_body(response: &mut Response<Body>) -> Result<String, IpApiError> {
-        let Some(body) = response.body_mut().data().await else {
+    async fn collect_body(&self, body: &mut Body) -> Result<Vec<u8>, IpApiError> {
+        let mut bytes = Vec::new();
+
+        while let Some(chunk) = body.data().await {
+            let Ok(chunk) = chunk else {
+                return Err(IpApiError::ConnectionLost);
+            };
+
+            if bytes.len() + chunk.len() > self.max_response_bytes {
+                return Err(IpApiError::ResponseTooLarge);
+            }
+
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(bytes)
+    }
+
+    async fn parse_response_body(
+        &self,
+        response: &mut Response<Body>,
+    ) -> Result<String, IpApiError> {
+        let body = self.collect_body(response.body_mut());
+        let body = match self.read_timeout {
+            Some(read_timeout) => match tokio::time::timeout(read_timeout, body).await {
+                Ok(body) => body?,
+                Err(_) => return Err(IpApiError::Timeout),
+            },
+            None => body.await?,
+        };
+
+        if body.is_empty() {
+            if response.status().is_success() {
+                return Err(IpApiError::EmptyResponse);
+            }
+
             return Err(IpApiError::UnexpectedError(Some(
                 "Response is empty".into(),
             )));
+        }
+
+        #[cfg(feature = "compression")]
+        let body = self.decompress_body(response.headers(), body)?;
+
+        let Ok(body) = String::from_utf8(body) else {
+            return Err(IpApiError::UnexpectedError(Some(
+                "Failed to convert body from the response to String".into(),
+            )));
+        };
+
+        Ok(body)
+    }
+
+    #[cfg(feature = "compression")]
+    fn decompress_body(&self, headers: &HeaderMap, body: Vec<u8>) -> Result<Vec<u8>, IpApiError> {
+        use flate2::read::{GzDecoder, ZlibDecoder};
+        use std::io::Read;
+
+        let encoding = headers
+            .get(hyper::header::CONTENT_ENCODING)
+            .and_then(|encoding| encoding.to_str().ok())
+            .map(|encoding| encoding.trim().to_ascii_lowercase());
+
+        // One byte more than the maximum to detect oversized bodies
+        let limit = self.max_response_bytes as u64 + 1;
+        let mut decompressed = Vec::new();
+        let result = match encoding.as_deref() {
+            Some("gzip") => GzDecoder::new(body.as_slice())
+                .take(limit)
+                .read_to_end(&mut decompressed),
+            Some("deflate") => ZlibDecoder::new(body.as_slice())
+                .take(limit)
+                .read_to_end(&mut decompressed),
+            _ => return Ok(body),
         };
-        let Ok(body) = body else {
+
+        if result.is_err() {
             return Err(IpApiError::UnexpectedError(Some(
-                "Failed to retrieve body from the response".into(),
+                "Failed to decompress body from the response".into(),
             )));
+        }
+
+        if decompressed.len() > self.max_response_bytes {
+            return Err(IpApiError::ResponseTooLarge);
+        }
+
+        Ok(decompressed)
+    }
+
+    fn build_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if cfg!(feature = "compression") {
+            headers.insert(
+                hyper::header::ACCEPT_ENCODING,
+                hyper::header::HeaderValue::from_static("gzip, deflate"),
+            );
+        }
+
+        headers
+    }
+
+    fn prepare(
+        &self,
+        mode: RequestMode,
+        target: Option<&str>,
+        body: Option<String>,
+    ) -> PreparedRequest {
+        let mut headers = Self::build_headers();
+
+        if body.is_some() {
+            headers.insert(
+                hyper::header::CONTENT_TYPE,
+                hyper::header::HeaderValue::from_static("application/json"),
+            );
+        }
+
+        PreparedRequest {
+            method: mode.method(),
+            url: Self::build_uri(
+                mode,
+                target,
+                &self.fields_query_value(),
+                self.language.clone(),
+            ),
+            headers,
+            body,
+        }
+    }
+
+    fn prepare_request(&self, target: &str) -> PreparedRequest {
+        self.prepare(RequestMode::Single, Some(target), None)
+    }
+
+    fn prepare_batch_request(&self, body: String) -> PreparedRequest {
+        self.prepare(RequestMode::Batch, None, Some(body))
+    }
+
+    /// Establish a connection to [ip-api.com API](https://ip-api.com/docs/api:json) in advance,
+    /// so the next request doesn't spend time on it
+    ///
+    /// The connection is kept by the HTTP client of the config, which is shared by its clones.
+    ///
+    /// # Notice
+    ///
+    /// It makes a minimal request for your IP, so it counts as one request against the rate limit
+    pub async fn warm_up(&self) -> Result<(), IpApiError> {
+        let request = PreparedRequest {
+            url: Self::build_uri(
+                RequestMode::Single,
+                None,
+                &IpDataField::Message.bit().to_string(),
+                IpApiLanguage::En,
+            ),
+            ..self.prepare_request("")
         };
-        let Ok(body) = String::from_utf8(body.to_vec()) else {
+
+        let response = &mut self.send(request.into_request()?).await?;
+
+        Self::check_response(response)?;
+        self.parse_response_body(response).await?;
+
+        Ok(())
+    }
+
+    /// Build the request for `target` without sending it
+    ///
+    /// Useful for checking which URL and headers [`make_request`](Self::make_request)
+    /// would use for this config
+    pub fn dry_run(self, target: &str) -> PreparedRequest {
+        self.prepare_request(target)
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_metrics<T>(result: &Result<T, IpApiError>) {
+        metrics::counter!("ip_api_requests_total").increment(1);
+
+        if let Err(error) = result {
+            metrics::counter!("ip_api_errors_total", "kind" => error.kind()).increment(1);
+
+            if let IpApiError::RateLimit(_) | IpApiError::RateLimitPartial { .. } = error {
+                metrics::counter!("ip_api_rate_limited_total").increment(1);
+            }
+        }
+    }
+
+    fn parse_json<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, IpApiError> {
+        let Ok(value) = serde_json::from_str(body) else {
             return Err(IpApiError::UnexpectedError(Some(
-                "Failed to convert body from the response to String".into(),
+                "Failed to parse body from the response".into(),
             )));
         };
 
-        Ok(body)
+        Ok(value)
+    }
+
+    /// `targets` are the targets of the request in order, as passed by the caller
+    fn parse_ip_data(
+        mode: RequestMode,
+        targets: &[&str],
+        body: &str,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        let target_at = |index: usize| targets.get(index).copied().unwrap_or_default();
+
+        match mode {
+            RequestMode::Single => {
+                Self::check_error_message(Self::parse_json(body)?, target_at(0))?;
+
+                Ok(vec![Self::parse_json(body)?])
+            }
+            RequestMode::Batch => {
+                let messages: Vec<IpApiMessage> = Self::parse_json(body)?;
+
+                for (index, message) in messages.into_iter().enumerate() {
+                    Self::check_error_message(message, target_at(index))?;
+                }
+
+                Self::parse_json(body)
+            }
+        }
+    }
+
+    async fn send_in_mode(
+        &self,
+        mode: RequestMode,
+        targets: &[&str],
+        request: PreparedRequest,
+    ) -> Result<(Vec<IpData>, HeaderMap), IpApiError> {
+        self.validate().map_err(IpApiError::InvalidConfig)?;
+
+        let (headers, body) = self
+            .send_with_retries(request, |mut response| async move {
+                let body = self.parse_response_body(&mut response).await?;
+
+                Ok((response.headers().clone(), body))
+            })
+            .await?;
+
+        Ok((Self::parse_ip_data(mode, targets, &body)?, headers))
+    }
+
+    async fn send_request(&self, target: &str) -> Result<(IpData, HeaderMap), IpApiError> {
+        let (mut ip_batch_data, headers) = self
+            .send_in_mode(RequestMode::Single, &[target], self.prepare_request(target))
+            .await?;
+
+        let Some(ip_data) = ip_batch_data.pop() else {
+            return Err(IpApiError::EmptyResponse);
+        };
+
+        self.check_missing_fields(&ip_data)?;
+
+        Ok((ip_data, headers))
     }
 
     /// Making a request to [ip-api.com API](https://ip-api.com/docs/api:json)
     ///
-    /// `target` can be "ip"/"domain"/"empty string (if you want to request your ip)"
+    /// `target` can be "ip"/"domain"/"empty string (if you want to request your ip)".
+    /// Domains are lowercased and stripped of a trailing dot (so `Example.COM.` and
+    /// `example.com` lead to the same request), IPs are left untouched.
+    /// If [the cache](Self::set_cache) is enabled, the result is taken from it when possible
     pub async fn make_request(self, target: &str) -> Result<IpData, IpApiError> {
-        let uri = Self::build_uri("json", Some(target), self.numeric_field, self.language);
+        self.make_request_cached(target)
+            .await
+            .map(|(ip_data, _)| ip_data)
+    }
 
-        let client = Client::new();
-        let Ok(uri) = uri.parse() else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to parse request URI".into(),
-            )));
+    /// Making a request like [`make_request`](Self::make_request)
+    /// and return whether the result was taken from [the cache](Self::set_cache)
+    ///
+    /// Useful for measuring the cache hit rate.
+    /// Without the cache the status is always [`CacheStatus::Miss`]
+    pub async fn make_request_cached(
+        self,
+        target: &str,
+    ) -> Result<(IpData, CacheStatus), IpApiError> {
+        let key = self.prepare_request(target).url;
+        let cache = self.cache.clone();
+
+        if let Some(cache) = &cache {
+            let cache = cache.lock().unwrap_or_else(|error| error.into_inner());
+
+            if let Some(ip_data) = cache.get(&key) {
+                return Ok((ip_data, CacheStatus::Hit));
+            }
+        }
+
+        let (ip_data, _) = self.make_request_with_headers(target).await?;
+
+        if let Some(cache) = &cache {
+            let mut cache = cache.lock().unwrap_or_else(|error| error.into_inner());
+            cache.insert(key, ip_data.clone());
+        }
+
+        Ok((ip_data, CacheStatus::Miss))
+    }
+
+    /// Making a request like [`make_request`](Self::make_request)
+    /// and return all headers of the response as well
+    ///
+    /// Useful for debugging requests that go through CDNs or proxies.
+    /// [The cache](Self::set_cache) is bypassed, since it doesn't keep headers
+    pub async fn make_request_with_headers(
+        self,
+        target: &str,
+    ) -> Result<(IpData, HeaderMap), IpApiError> {
+        let result = self.send_request(target).await;
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
+
+    /// Making a request like [`make_request`](Self::make_request),
+    /// but with `fields` instead of the fields of the config for this request only
+    ///
+    /// Other settings (language, timeouts, the HTTP client, etc.) are kept,
+    /// and the config doesn't need to be cloned.
+    /// [`IpDataField::Message`] is always included, since it is required to detect errors
+    pub async fn make_request_with_fields(
+        &self,
+        target: &str,
+        fields: &[IpDataField],
+    ) -> Result<IpData, IpApiError> {
+        let numeric_field = fields
+            .iter()
+            .fold(IpDataField::Message.bit(), |mask, field| mask | field.bit());
+        let request = PreparedRequest {
+            url: Self::build_uri(
+                RequestMode::Single,
+                Some(target),
+                &numeric_field.to_string(),
+                self.language.clone(),
+            ),
+            ..self.prepare_request(target)
         };
-        let Ok(response) = &mut client.get(uri).await else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to make a request".into(),
-            )));
+
+        let result = self
+            .send_in_mode(RequestMode::Single, &[target], request)
+            .await
+            .and_then(|(mut ip_batch_data, _)| {
+                let Some(ip_data) = ip_batch_data.pop() else {
+                    return Err(IpApiError::EmptyResponse);
+                };
+                let missing_fields = Self::missing_fields_in(numeric_field, &ip_data);
+
+                if self.is_strict && !missing_fields.is_empty() {
+                    return Err(IpApiError::MissingFields(missing_fields));
+                }
+
+                Ok(ip_data)
+            });
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
+
+    /// Making a request like [`make_request`](Self::make_request)
+    /// and return the requested fields that are missing in the response
+    ///
+    /// A missing field (`None`) means that it was requested, but the API has no data for it
+    /// (common for fields like [`zip`](struct.IpData.html#structfield.zip) or
+    /// [`district`](struct.IpData.html#structfield.district)).
+    /// Unlike [`strict`](Self::strict), missing fields never lead to an error.
+    /// [The cache](Self::set_cache) is bypassed
+    pub async fn make_request_report(
+        self,
+        target: &str,
+    ) -> Result<(IpData, Vec<IpDataField>), IpApiError> {
+        let result = self
+            .send_in_mode(RequestMode::Single, &[target], self.prepare_request(target))
+            .await
+            .and_then(|(mut ip_batch_data, _)| {
+                let Some(ip_data) = ip_batch_data.pop() else {
+                    return Err(IpApiError::EmptyResponse);
+                };
+                let missing_fields = self.missing_fields(&ip_data);
+
+                Ok((ip_data, missing_fields))
+            });
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
+
+    /// Making requests for `targets` in order and return the first successful result
+    ///
+    /// Only errors related to the target itself ([`IpApiError::InvalidQuery`],
+    /// [`IpApiError::DomainNotResolved`], [`IpApiError::PrivateRange`],
+    /// [`IpApiError::ReservedRange`] and [`IpApiError::MissingFields`]) lead to trying
+    /// the next target, since other errors (e.g. [`IpApiError::RateLimit`]) would fail
+    /// the next targets too and are returned immediately.
+    /// If all targets fail, the last error is returned
+    pub async fn make_request_any(self, targets: &[&str]) -> Result<IpData, IpApiError> {
+        let mut last_error = IpApiError::UnexpectedError(Some("No targets to request".into()));
+
+        for target in targets {
+            match self.clone().make_request(target).await {
+                Ok(ip_data) => return Ok(ip_data),
+                Err(
+                    error @ (IpApiError::InvalidQuery
+                    | IpApiError::DomainNotResolved(_)
+                    | IpApiError::PrivateRange
+                    | IpApiError::ReservedRange
+                    | IpApiError::MissingFields(_)),
+                ) => last_error = error,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Making a request like [`make_request`](Self::make_request),
+    /// but abort it as soon as `token` is cancelled
+    ///
+    /// Returns [`IpApiError::Cancelled`] if `token` fires before the response is received
+    pub async fn make_request_with_cancel(
+        self,
+        target: &str,
+        token: CancellationToken,
+    ) -> Result<IpData, IpApiError> {
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(IpApiError::Cancelled),
+            result = self.make_request(target) => result,
+        }
+    }
+
+    /// Making a request like [`make_request`](Self::make_request)
+    /// and return the IP address that `target` was resolved to
+    ///
+    /// [`query`](struct.IpData.html#structfield.query) is always included.
+    /// The IP address is returned only if `target` is a domain
+    /// (so [`query`](struct.IpData.html#structfield.query) differs from `target`)
+    pub async fn make_request_resolved(
+        self,
+        target: &str,
+    ) -> Result<(IpData, Option<IpAddr>), IpApiError> {
+        let ip_data = self.include_query().make_request(target).await?;
+
+        let resolved = match ip_data.query.as_deref() {
+            Some(query) if query != target && target.parse::<IpAddr>().is_err() => {
+                query.parse().ok()
+            }
+            _ => None,
         };
 
-        Self::check_response(response)?;
+        Ok((ip_data, resolved))
+    }
+
+    async fn send_batch_request(
+        &self,
+        targets: &[&str],
+        body: String,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        self.send_in_mode(
+            RequestMode::Batch,
+            targets,
+            self.prepare_batch_request(body),
+        )
+        .await
+        .map(|(ip_batch_data, _)| ip_batch_data)
+    }
+
+    fn check_batch_missing_fields(
+        &self,
+        ip_batch_data: Vec<IpData>,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        for ip_data in &ip_batch_data {
+            self.check_missing_fields(ip_data)?;
+        }
+
+        Ok(ip_batch_data)
+    }
+
+    fn check_ipv4_range(ip: Ipv4Addr) -> Result<(), IpApiError> {
+        if ip.is_private() {
+            return Err(IpApiError::PrivateRange);
+        }
+
+        if ip.is_unspecified()
+            || ip.is_loopback()
+            || ip.is_link_local()
+            || ip.is_broadcast()
+            || ip.is_documentation()
+            || ip.is_multicast()
+            || ip.octets()[0] == 0
+            || ip.octets()[0] >= 240
+        {
+            return Err(IpApiError::ReservedRange);
+        }
+
+        Ok(())
+    }
+
+    fn check_ipv6_range(ip: Ipv6Addr) -> Result<(), IpApiError> {
+        if let Some(ip) = ip.to_ipv4_mapped() {
+            return Self::check_ipv4_range(ip);
+        }
+
+        // Unique local addresses (fc00::/7)
+        if ip.segments()[0] & 0xfe00 == 0xfc00 {
+            return Err(IpApiError::PrivateRange);
+        }
+
+        // Link-local (fe80::/10) and documentation (2001:db8::/32) addresses
+        if ip.is_unspecified()
+            || ip.is_loopback()
+            || ip.is_multicast()
+            || ip.segments()[0] & 0xffc0 == 0xfe80
+            || ip.segments()[..2] == [0x2001, 0xdb8]
+        {
+            return Err(IpApiError::ReservedRange);
+        }
+
+        Ok(())
+    }
+
+    fn check_ip_range(target: &str) -> Result<(), IpApiError> {
+        match target.parse() {
+            Ok(IpAddr::V4(ip)) => Self::check_ipv4_range(ip),
+            Ok(IpAddr::V6(ip)) => Self::check_ipv6_range(ip),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn apply_batch_invalid_policy<'a>(
+        &self,
+        targets: Vec<&'a str>,
+    ) -> Result<Vec<&'a str>, IpApiError> {
+        self.apply_batch_invalid_policy_to(targets, |target| target)
+    }
+
+    /// Apply the policy to `items` with targets taken by `target_of`
+    fn apply_batch_invalid_policy_to<T>(
+        &self,
+        items: Vec<T>,
+        target_of: impl Fn(&T) -> &str,
+    ) -> Result<Vec<T>, IpApiError> {
+        match self.batch_invalid_policy {
+            BatchInvalidPolicy::Error => {
+                for item in &items {
+                    Self::check_ip_range(target_of(item))?;
+                }
+
+                Ok(items)
+            }
+            BatchInvalidPolicy::Skip => Ok(items
+                .into_iter()
+                .filter(|item| Self::check_ip_range(target_of(item)).is_ok())
+                .collect()),
+            BatchInvalidPolicy::Passthrough => Ok(items),
+        }
+    }
+
+    /// Send `items` in consecutive requests of at most 100 entries built by `entry`,
+    /// `check` checks the results of every request against its items
+    ///
+    /// If the items are split into several requests, errors carry the results
+    /// of the succeeded requests (see [`IpApiError::RateLimitPartial`]
+    /// and [`IpApiError::BatchPartial`])
+    async fn send_chunked_batch_request<T>(
+        &self,
+        items: Vec<T>,
+        target_of: impl Fn(&T) -> &str,
+        entry: impl Fn(&T) -> BatchEntry<'_>,
+        check: impl Fn(&[T], Vec<IpData>) -> Result<Vec<IpData>, IpApiError>,
+    ) -> Result<(Vec<IpData>, HeaderMap), IpApiError> {
+        let items = self.apply_batch_invalid_policy_to(items, &target_of)?;
+        let is_split = items.len() > BATCH_REQUEST_LIMIT;
+        let mut completed = Vec::with_capacity(items.len());
+        let mut last_headers = HeaderMap::new();
+
+        for chunk in items.chunks(BATCH_REQUEST_LIMIT) {
+            let targets: Vec<&str> = chunk.iter().map(&target_of).collect();
+            let body = Self::build_batch_body(chunk.iter().map(&entry).collect());
+            let result = self
+                .send_in_mode(
+                    RequestMode::Batch,
+                    &targets,
+                    self.prepare_batch_request(body),
+                )
+                .await
+                .and_then(|(ip_batch_data, headers)| Ok((check(chunk, ip_batch_data)?, headers)));
+
+            match result {
+                Ok((ip_batch_data, headers)) => {
+                    completed.extend(ip_batch_data);
+                    last_headers = headers;
+                }
+                Err(IpApiError::RateLimit(ttl)) if is_split => {
+                    return Err(IpApiError::RateLimitPartial {
+                        completed,
+                        retry_in: Duration::from_secs(ttl.into()),
+                    });
+                }
+                Err(error) if is_split => {
+                    return Err(IpApiError::BatchPartial {
+                        completed,
+                        error: Box::new(error),
+                    });
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok((completed, last_headers))
+    }
+
+    /// Making a batch request to [ip-api.com API](https://ip-api.com/docs/api:batch)
+    ///
+    /// `target` can be "IPv4"/"IPv6".
+    /// If there are more than 100 targets (the API limit),
+    /// they are split into several consecutive requests, and errors carry the results
+    /// of the previous requests (see [`IpApiError::RateLimitPartial`]
+    /// and [`IpApiError::BatchPartial`])
+    pub async fn make_batch_request(self, targets: Vec<&str>) -> Result<Vec<IpData>, IpApiError> {
+        self.make_batch_request_with_meta(targets)
+            .await
+            .map(|(ip_batch_data, _)| ip_batch_data)
+    }
+
+    /// Making a batch request like [`make_batch_request`](Self::make_batch_request)
+    /// and return the rate limit state reported by the API as well
+    ///
+    /// If the targets are split into several requests, the state is taken from the last one,
+    /// so it shows the remaining budget after the whole batch.
+    /// Useful for deciding whether the next batch can be sent right away
+    pub async fn make_batch_request_with_meta(
+        self,
+        targets: Vec<&str>,
+    ) -> Result<(Vec<IpData>, RateLimitInfo), IpApiError> {
+        let result = self
+            .send_chunked_batch_request(
+                targets,
+                |target| target,
+                |target| BatchEntry::new(target),
+                |_, ip_batch_data| self.check_batch_missing_fields(ip_batch_data),
+            )
+            .await
+            .map(|(ip_batch_data, headers)| (ip_batch_data, RateLimitInfo::from_headers(&headers)));
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
+
+    async fn write_batch_lines(
+        &self,
+        targets: &[&str],
+        writer: &mut impl io::Write,
+    ) -> Result<(), IpApiError> {
+        let targets = self.apply_batch_invalid_policy(targets.to_vec())?;
+
+        for chunk in targets.chunks(BATCH_REQUEST_LIMIT) {
+            let body = Self::build_batch_body(
+                chunk.iter().map(|target| BatchEntry::new(target)).collect(),
+            );
+            let ip_batch_data = self.send_batch_request(chunk, body).await?;
+
+            for ip_data in self.check_batch_missing_fields(ip_batch_data)? {
+                let is_written = serde_json::to_writer(&mut *writer, &ip_data).is_ok()
+                    && writer.write_all(b"\n").is_ok();
+
+                if !is_written {
+                    return Err(IpApiError::UnexpectedError(Some(
+                        "Failed to write to the writer".into(),
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Making a batch request like [`make_batch_request`](Self::make_batch_request)
+    /// and write the results to `writer` as [JSON Lines](https://jsonlines.org/)
+    ///
+    /// Every 100 targets are requested and written before requesting the next ones,
+    /// so all results are never kept in memory at once.
+    ///
+    /// # Notice
+    ///
+    /// If an error occurs in the middle (e.g. [`IpApiError::RateLimit`]),
+    /// the results of the previous requests are already written
+    pub async fn lookup_to_writer(
+        self,
+        targets: &[&str],
+        writer: &mut impl io::Write,
+    ) -> Result<(), IpApiError> {
+        let result = self.write_batch_lines(targets, writer).await;
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
+
+    /// Making a batch request like [`make_batch_request`](Self::make_batch_request)
+    /// and split the results by protocol family
+    ///
+    /// Returns `(ipv4_results, ipv6_results)`, each in the same order as in `targets`.
+    /// Every target must be an IP address, domains (and anything else that isn't
+    /// an IP address) lead to [`IpApiError::InvalidQuery`] before any request is made
+    pub async fn make_batch_request_by_family(
+        self,
+        targets: Vec<&str>,
+    ) -> Result<(Vec<IpData>, Vec<IpData>), IpApiError> {
+        let targets = self.apply_batch_invalid_policy(targets)?;
+        let mut is_ipv4 = Vec::with_capacity(targets.len());
+
+        for target in &targets {
+            match target.parse::<IpAddr>() {
+                Ok(ip) => is_ipv4.push(ip.is_ipv4()),
+                Err(_) => return Err(IpApiError::InvalidQuery),
+            }
+        }
+
+        let ip_batch_data = self.make_batch_request(targets).await?;
+        let (ipv4, ipv6): (Vec<_>, Vec<_>) = ip_batch_data
+            .into_iter()
+            .zip(is_ipv4)
+            .partition(|(_, is_ipv4)| *is_ipv4);
+
+        Ok((
+            ipv4.into_iter().map(|(ip_data, _)| ip_data).collect(),
+            ipv6.into_iter().map(|(ip_data, _)| ip_data).collect(),
+        ))
+    }
+
+    #[cfg(feature = "stream")]
+    async fn send_batch_stream_request(&self, body: String) -> Result<Body, IpApiError> {
+        self.validate().map_err(IpApiError::InvalidConfig)?;
+
+        // The body is parsed while it's being received, so it must not be compressed
+        let mut request = self.prepare_batch_request(body);
+        request.headers.remove(hyper::header::ACCEPT_ENCODING);
+
+        self.send_with_retries(request, |response| async { Ok(response.into_body()) })
+            .await
+    }
+
+    /// Making a batch request like [`make_batch_request`](Self::make_batch_request),
+    /// but parse the results incrementally while the response is being received
+    ///
+    /// Only the received part of the current result is kept in memory instead of
+    /// the whole body. The requests are sent lazily while the stream is polled,
+    /// one per 100 targets
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use ip_api_client as Client;
+    ///
+    /// let mut stream = Client::generate_empty_config()
+    ///     .include_isp()
+    ///     .make_batch_stream(vec!["1.1.1.1", "8.8.8.8"])
+    ///     .unwrap();
+    ///
+    /// while let Some(ip_data) = stream.next().await {
+    ///     println!("{:?}", ip_data.unwrap().isp);
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "stream")]
+    pub fn make_batch_stream(self, targets: Vec<&str>) -> Result<BatchStream, IpApiError> {
+        let requests = self
+            .apply_batch_invalid_policy(targets)?
+            .chunks(BATCH_REQUEST_LIMIT)
+            .map(|chunk| {
+                let body = Self::build_batch_body(
+                    chunk.iter().map(|target| BatchEntry::new(target)).collect(),
+                );
+
+                (
+                    body,
+                    chunk.iter().map(|target| target.to_string()).collect(),
+                )
+            })
+            .collect();
+
+        Ok(BatchStream::new(self, requests))
+    }
+
+    async fn send_localized_batch_request(
+        &self,
+        items: Vec<(&str, IpApiLanguage)>,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        self.send_chunked_batch_request(
+            items,
+            |(target, _)| target,
+            |(target, language)| BatchEntry {
+                lang: Some(language.code()),
+                ..BatchEntry::new(target)
+            },
+            |_, ip_batch_data| self.check_batch_missing_fields(ip_batch_data),
+        )
+        .await
+        .map(|(ip_batch_data, _)| ip_batch_data)
+    }
 
-        let body = Self::parse_response_body(response).await?;
-        let Ok(ip_data): Result<IpApiMessage, _> = serde_json::from_str(body.as_str()) else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to parse body from the response".into(),
-            )));
-        };
+    /// Making a batch request to [ip-api.com API](https://ip-api.com/docs/api:batch)
+    /// with an individual language for every target
+    ///
+    /// `target` can be "IPv4"/"IPv6".
+    /// The results are in the same order as `items`,
+    /// [the batch invalid policy](Self::set_batch_invalid_policy) is applied to `items`.
+    /// More than 100 items are split into several requests
+    /// like in [`make_batch_request`](Self::make_batch_request)
+    pub async fn make_batch_request_localized(
+        self,
+        items: Vec<(&str, IpApiLanguage)>,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        let result = self.send_localized_batch_request(items).await;
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
 
-        Self::check_error_message(ip_data.message)?;
+    async fn send_mixed_batch_request(
+        &self,
+        items: Vec<(&str, IpApiConfig)>,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        let check = |items: &[(&str, IpApiConfig)], ip_batch_data: Vec<IpData>| {
+            if self.is_strict {
+                for (ip_data, (_, config)) in ip_batch_data.iter().zip(items) {
+                    let missing_fields = config.missing_fields(ip_data);
+
+                    if !missing_fields.is_empty() {
+                        return Err(IpApiError::MissingFields(missing_fields));
+                    }
+                }
+            }
 
-        let Ok(ip_data): Result<IpData, _> = serde_json::from_str(body.as_str()) else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to parse body from the response".into(),
-            )));
+            Ok(ip_batch_data)
         };
 
-        Ok(ip_data)
+        self.send_chunked_batch_request(
+            items,
+            |(target, _)| target,
+            |(target, config)| BatchEntry {
+                fields: Some(config.fields_names()),
+                ..BatchEntry::new(target)
+            },
+            check,
+        )
+        .await
+        .map(|(ip_batch_data, _)| ip_batch_data)
     }
 
     /// Making a batch request to [ip-api.com API](https://ip-api.com/docs/api:batch)
+    /// with individual fields for every target
     ///
-    /// `target` can be "IPv4"/"IPv6"
-    pub async fn make_batch_request(self, targets: Vec<&str>) -> Result<Vec<IpData>, IpApiError> {
-        let uri = Self::build_uri("batch", None, self.numeric_field, self.language);
+    /// `target` can be "IPv4"/"IPv6".
+    /// Only fields are taken from the configs of `items`, they are sent per entry as
+    /// `{"query":"1.1.1.1","fields":"country,city"}`.
+    /// The results are in the same order as `items`,
+    /// [the batch invalid policy](Self::set_batch_invalid_policy) is applied to `items`.
+    /// More than 100 items are split into several requests
+    /// like in [`make_batch_request`](Self::make_batch_request)
+    pub async fn make_batch_request_mixed(
+        self,
+        items: Vec<(&str, IpApiConfig)>,
+    ) -> Result<Vec<IpData>, IpApiError> {
+        let result = self.send_mixed_batch_request(items).await;
+
+        #[cfg(feature = "metrics")]
+        Self::record_metrics(&result);
+
+        result
+    }
 
-        let Ok(request) = Request::builder()
-            .method(Method::POST)
-            .uri(uri)
-            .header("content-type", "application/json")
-            .body(Body::from(json!(targets).to_string()))
-        else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to build a request".into(),
-            )));
-        };
+    fn include_field(self, field: IpDataField) -> Self {
+        match field {
+            IpDataField::Continent => self.include_continent(),
+            IpDataField::ContinentCode => self.include_continent_code(),
+            IpDataField::Country => self.include_country(),
+            IpDataField::CountryCode => self.include_country_code(),
+            IpDataField::Region => self.include_region(),
+            IpDataField::RegionName => self.include_region_name(),
+            IpDataField::City => self.include_city(),
+            IpDataField::District => self.include_district(),
+            IpDataField::Zip => self.include_zip(),
+            IpDataField::Lat => self.include_lat(),
+            IpDataField::Lon => self.include_lon(),
+            IpDataField::Timezone => self.include_timezone(),
+            IpDataField::Offset => self.include_offset(),
+            IpDataField::Currency => self.include_currency(),
+            IpDataField::Isp => self.include_isp(),
+            IpDataField::Org => self.include_org(),
+            IpDataField::AsField => self.include_as_field(),
+            IpDataField::Asname => self.include_asname(),
+            IpDataField::Reverse => self.include_reverse(),
+            IpDataField::Mobile => self.include_mobile(),
+            IpDataField::Proxy => self.include_proxy(),
+            IpDataField::Hosting => self.include_hosting(),
+            IpDataField::Query => self.include_query(),
+            IpDataField::Message => self,
+        }
+    }
 
-        let client = Client::new();
-        let Ok(response) = &mut client.request(request).await else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to make a request".into(),
-            )));
-        };
+    /// Include fields by their names used in the
+    /// [ip-api.com API](https://ip-api.com/docs/api:json#returned-data) JSON response
+    /// (see [`IpDataField::name`])
+    ///
+    /// Useful for loading fields from config files, e.g. `["country", "city", "isp"]`.
+    /// Returns [`ConfigError::UnknownFieldNames`] with all unrecognized names
+    pub fn include_field_names(mut self, names: &[&str]) -> Result<Self, ConfigError> {
+        let mut unknown_names = Vec::new();
+
+        for name in names {
+            match IpDataField::from_name(name) {
+                Some(field) => self = self.include_field(field),
+                None => unknown_names.push(name.to_string()),
+            }
+        }
 
-        Self::check_response(response)?;
+        if !unknown_names.is_empty() {
+            return Err(ConfigError::UnknownFieldNames(unknown_names));
+        }
 
-        let body = Self::parse_response_body(response).await?;
-        let Ok(ip_batch_data): Result<Vec<IpApiMessage>, _> = serde_json::from_str(body.as_str())
-        else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to parse body from the response".into(),
-            )));
-        };
+        Ok(self)
+    }
+
+    /// Create a config that includes only one `field`
+    ///
+    /// [`IpDataField::Message`] is always kept, since it is required to detect errors
+    pub fn with_only(field: IpDataField) -> Self {
+        generate_empty_config().include_field(field)
+    }
+
+    /// Request only security-relevant fields of `target`
+    ///
+    /// Includes only [`proxy`](struct.IpData.html#structfield.proxy),
+    /// [`mobile`](struct.IpData.html#structfield.mobile),
+    /// [`hosting`](struct.IpData.html#structfield.hosting)
+    /// and [`query`](struct.IpData.html#structfield.query) to save traffic.
+    /// Absent flags are treated as `false`
+    pub async fn security_check(target: &str) -> Result<SecurityReport, IpApiError> {
+        let ip_data = generate_empty_config()
+            .include_proxy()
+            .include_mobile()
+            .include_hosting()
+            .include_query()
+            .make_request(target)
+            .await?;
+
+        Ok(SecurityReport {
+            query: ip_data.query.unwrap_or_else(|| target.into()),
+            is_proxy: ip_data.proxy.unwrap_or(false),
+            is_mobile: ip_data.mobile.unwrap_or(false),
+            is_hosting: ip_data.hosting.unwrap_or(false),
+        })
+    }
+
+    /// Request only security-relevant fields of `target` and return
+    /// [the risk score](IpData::risk_score)
+    ///
+    /// Includes only [`proxy`](struct.IpData.html#structfield.proxy),
+    /// [`mobile`](struct.IpData.html#structfield.mobile) and
+    /// [`hosting`](struct.IpData.html#structfield.hosting) to save traffic.
+    /// The score is a heuristic, not a validated fraud model
+    pub async fn security_score(target: &str) -> Result<u8, IpApiError> {
+        let ip_data = generate_empty_config()
+            .include_proxy()
+            .include_mobile()
+            .include_hosting()
+            .make_request(target)
+            .await?;
+
+        Ok(ip_data.risk_score())
+    }
+
+    /// Request only network-relevant fields of `target`
+    ///
+    /// Includes only [`isp`](struct.IpData.html#structfield.isp),
+    /// [`as_field`](struct.IpData.html#structfield.as_field),
+    /// [`org`](struct.IpData.html#structfield.org)
+    /// and [`query`](struct.IpData.html#structfield.query) to save traffic.
+    /// The AS number and organization are parsed from
+    /// [`as_field`](struct.IpData.html#structfield.as_field)
+    pub async fn network_of(target: &str) -> Result<NetworkIdentity, IpApiError> {
+        let ip_data = generate_empty_config()
+            .include_isp()
+            .include_as_field()
+            .include_org()
+            .include_query()
+            .make_request(target)
+            .await?;
+
+        Ok(NetworkIdentity {
+            asn: ip_data.asn(),
+            as_org: ip_data.as_org().map(Into::into),
+            query: ip_data.query.unwrap_or_else(|| target.into()),
+            isp: ip_data.isp,
+            org: ip_data.org,
+        })
+    }
 
-        for ip_data in ip_batch_data {
-            Self::check_error_message(ip_data.message)?;
+    /// Check whether `target` is known to the API
+    ///
+    /// Uses [`generate_query_only_config`] to transfer almost no data.
+    /// Returns `false` on [`IpApiError::InvalidQuery`] and
+    /// [`IpApiError::DomainNotResolved`], other errors are returned as is
+    pub async fn exists(target: &str) -> Result<bool, IpApiError> {
+        match generate_query_only_config().make_request(target).await {
+            Ok(_) => Ok(true),
+            Err(IpApiError::InvalidQuery | IpApiError::DomainNotResolved(_)) => Ok(false),
+            Err(error) => Err(error),
         }
+    }
 
-        let Ok(ip_batch_data): Result<Vec<IpData>, _> = serde_json::from_str(body.as_str()) else {
-            return Err(IpApiError::UnexpectedError(Some(
-                "Failed to parse body from the response".into(),
-            )));
+    /// Check whether `target` is located in one of the `allowed` countries
+    ///
+    /// `allowed` contains two-letter country codes
+    /// [ISO 3166-1 alpha-2](https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2),
+    /// compared case-insensitively.
+    /// Only [`country_code`](struct.IpData.html#structfield.country_code) is requested
+    /// to save traffic
+    pub async fn country_in(target: &str, allowed: &[&str]) -> Result<bool, IpApiError> {
+        let ip_data = Self::with_only(IpDataField::CountryCode)
+            .make_request(target)
+            .await?;
+
+        let Some(country_code) = ip_data.country_code else {
+            return Ok(false);
         };
 
-        Ok(ip_batch_data)
+        Ok(allowed
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&country_code)))
     }
 
     /// Include [`continent`](struct.IpData.html#structfield.continent) in request
@@ -938,6 +6734,416 @@ impl IpApiConfig {
 
         self
     }
+
+    /// Enable strict mode
+    ///
+    /// In strict mode, a request fails with [`IpApiError::MissingFields`]
+    /// if any of the included fields is missing in the response.
+    ///
+    /// # Notice
+    ///
+    /// [ip-api.com API](https://ip-api.com/docs/api:json) legitimately omits some fields
+    /// (e.g. [`zip`](struct.IpData.html#structfield.zip) for some locations),
+    /// so strict mode is disabled by default
+    pub fn strict(mut self) -> Self {
+        self.is_strict = true;
+
+        self
+    }
+
+    /// Check the config for incoherent combinations of settings
+    ///
+    /// Called automatically before every request.
+    /// See [`ConfigError`] for all checked conditions
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validate_fields()?;
+
+        let has_fields = self
+            .included_fields()
+            .iter()
+            .any(|field| *field != IpDataField::Message);
+
+        if self.is_strict && !has_fields {
+            return Err(ConfigError::StrictWithoutFields);
+        }
+
+        Ok(())
+    }
+
+    /// Check that the fields mask contains only bits of [`IpDataField`]
+    /// (or the bit of `status`) and includes [`IpDataField::Message`]
+    ///
+    /// `status` isn't an [`IpDataField`], since errors are detected via `message`,
+    /// but it's accepted because the masks generated on the ip-api.com website include it.
+    /// Called automatically by [`validate`](Self::validate)
+    pub fn validate_fields(&self) -> Result<(), ConfigError> {
+        let recognized = IpDataField::ALL
+            .iter()
+            .fold(STATUS_FIELD_BIT, |mask, field| mask | field.bit());
+        let unrecognized = self.numeric_field & !recognized;
+
+        if unrecognized != 0 {
+            return Err(ConfigError::UnrecognizedFieldBits(unrecognized));
+        }
+
+        if self.numeric_field & IpDataField::Message.bit() == 0 {
+            return Err(ConfigError::MissingMessageField);
+        }
+
+        Ok(())
+    }
+
+    /// Set the raw `fields` mask of [ip-api.com API](https://ip-api.com/docs/api:json)
+    ///
+    /// Useful for reusing a mask generated on the ip-api.com website.
+    /// The mask is checked by [`validate_fields`](Self::validate_fields) before sending
+    pub fn set_fields_mask(mut self, mask: u32) -> Self {
+        let is_included = |field: IpDataField| mask & field.bit() != 0;
+
+        self.is_continent_included = is_included(IpDataField::Continent);
+        self.is_continent_code_included = is_included(IpDataField::ContinentCode);
+        self.is_country_included = is_included(IpDataField::Country);
+        self.is_country_code_included = is_included(IpDataField::CountryCode);
+        self.is_region_included = is_included(IpDataField::Region);
+        self.is_region_name_included = is_included(IpDataField::RegionName);
+        self.is_city_included = is_included(IpDataField::City);
+        self.is_district_included = is_included(IpDataField::District);
+        self.is_zip_included = is_included(IpDataField::Zip);
+        self.is_lat_included = is_included(IpDataField::Lat);
+        self.is_lon_included = is_included(IpDataField::Lon);
+        self.is_timezone_included = is_included(IpDataField::Timezone);
+        self.is_offset_included = is_included(IpDataField::Offset);
+        self.is_currency_included = is_included(IpDataField::Currency);
+        self.is_isp_included = is_included(IpDataField::Isp);
+        self.is_org_included = is_included(IpDataField::Org);
+        self.is_as_field_included = is_included(IpDataField::AsField);
+        self.is_asname_included = is_included(IpDataField::Asname);
+        self.is_reverse_included = is_included(IpDataField::Reverse);
+        self.is_mobile_included = is_included(IpDataField::Mobile);
+        self.is_proxy_included = is_included(IpDataField::Proxy);
+        self.is_hosting_included = is_included(IpDataField::Hosting);
+        self.is_query_included = is_included(IpDataField::Query);
+        self.numeric_field = mask;
+
+        self
+    }
+
+    /// Set how long to wait before retries within [the retry budget](Self::set_retry_budget)
+    ///
+    /// Waiting is counted against `max_total_wait` of the budget.
+    /// Jitter spreads out the retries of many instances rejected at the same moment.
+    /// [`Backoff::default`] (a fixed wait of 200ms) is used by default
+    pub fn set_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+
+        self
+    }
+
+    /// Set how batch requests handle targets that are private or reserved IP addresses,
+    /// see [`BatchInvalidPolicy`]
+    ///
+    /// Applies to every batch request, including the localized, mixed and streamed ones.
+    /// If all targets are skipped, nothing is sent.
+    /// The detection is local and covers the well-known ranges only
+    /// (e.g. 192.168.0.0/16, 127.0.0.0/8 or fc00::/7).
+    /// [`BatchInvalidPolicy::Passthrough`] is used by default
+    pub fn set_batch_invalid_policy(mut self, policy: BatchInvalidPolicy) -> Self {
+        self.batch_invalid_policy = policy;
+
+        self
+    }
+
+    /// Retry failed requests within a shared budget
+    ///
+    /// Requests rejected by the rate limit are retried after the time
+    /// returned by [ip-api.com API](https://ip-api.com/docs/api:json),
+    /// requests failed for a transient reason are retried after 200ms
+    /// (see [`set_backoff`](Self::set_backoff) to change both).
+    /// Transient are [`IpApiError::ConnectionFailed`], [`IpApiError::ConnectionLost`],
+    /// [`IpApiError::EmptyResponse`], [`IpApiError::Timeout`] and 5xx responses,
+    /// other errors (e.g. [`IpApiError::BindFailed`]) are returned immediately.
+    /// Both share the budget: at most `max_attempts` attempts (including the first one)
+    /// and at most `max_total_wait` of waiting in total.
+    /// When the budget is exceeded, the last error is returned.
+    /// There are no retries by default
+    pub fn set_retry_budget(mut self, max_attempts: u32, max_total_wait: Duration) -> Self {
+        self.retry_budget = Some(RetryBudget {
+            max_attempts,
+            max_total_wait,
+        });
+
+        self
+    }
+
+    /// Set a fallback base URL (e.g. `http://ip-api.example.com`) of a mirror
+    /// that serves the same API as [ip-api.com](https://ip-api.com/docs/api:json)
+    ///
+    /// If a request fails with [`IpApiError::ConnectionFailed`] or
+    /// [`IpApiError::ConnectionLost`], or the server responds with a 5xx status code,
+    /// it is repeated once against the fallback.
+    /// Other errors (e.g. [`IpApiError::QuotaExceeded`]) and other responses
+    /// (e.g. [`IpApiError::RateLimit`] or [`IpApiError::InvalidQuery`])
+    /// don't trigger the fallback, so it doesn't spend the quota or the pacing budget.
+    ///
+    /// Returns [`ConfigError::UnsupportedFallbackUrl`] if `base_url` isn't an absolute
+    /// `http` URL, since `https` isn't supported by the HTTP client
+    pub fn set_fallback_base_url(mut self, base_url: &str) -> Result<Self, ConfigError> {
+        let base_url = base_url.trim_end_matches('/');
+        let is_supported = base_url.parse::<Uri>().is_ok_and(|uri| {
+            uri.scheme_str() == Some("http")
+                && uri.host().is_some()
+                && matches!(uri.path(), "" | "/")
+                && uri.query().is_none()
+        });
+
+        if !is_supported {
+            return Err(ConfigError::UnsupportedFallbackUrl(base_url.into()));
+        }
+
+        self.fallback_base_url = Some(base_url.into());
+
+        Ok(self)
+    }
+
+    /// [`IpDataField::Message`] is always included
+    fn included_fields(&self) -> Vec<IpDataField> {
+        IpDataField::ALL
+            .into_iter()
+            .filter(|field| self.numeric_field & field.bit() != 0)
+            .collect()
+    }
+
+    /// Get fields included in request beyond [`generate_minimum_config`]
+    pub fn added_over_minimum(&self) -> Vec<IpDataField> {
+        self.difference(&generate_minimum_config())
+    }
+
+    /// Get fields included in request of this config but not of `other`
+    ///
+    /// Swap the configs to get the fields included only in `other`
+    pub fn difference(&self, other: &IpApiConfig) -> Vec<IpDataField> {
+        IpDataField::ALL
+            .into_iter()
+            .filter(|field| self.numeric_field & !other.numeric_field & field.bit() != 0)
+            .collect()
+    }
+
+    /// Check if both [`lat`](struct.IpData.html#structfield.lat)
+    /// and [`lon`](struct.IpData.html#structfield.lon) are included in request
+    pub fn will_return_coordinates(&self) -> bool {
+        let coordinates = IpDataField::Lat.bit() | IpDataField::Lon.bit();
+
+        self.numeric_field & coordinates == coordinates
+    }
+
+    /// Get the value of the `fields` URL parameter that is sent to
+    /// [ip-api.com API](https://ip-api.com/docs/api:json)
+    ///
+    /// Useful for reproducing requests in other tools
+    ///
+    /// # Example
+    ///
+    /// `32769` for a config that includes only [`country`](struct.IpData.html#structfield.country)
+    pub fn fields_query_value(&self) -> String {
+        self.numeric_field.to_string()
+    }
+
+    /// Estimate the size of a response (per IP) in bytes
+    ///
+    /// This is a rough estimate based on typical sizes of the included fields
+    /// (including their JSON keys), not a guarantee
+    pub fn estimated_response_bytes(&self) -> usize {
+        // Every field is sent as `"name":value,`
+        let fields_bytes: usize = self
+            .included_fields()
+            .iter()
+            .filter(|field| field.typical_value_bytes() > 0)
+            .map(|field| field.name().len() + field.typical_value_bytes() + 4)
+            .sum();
+
+        // Fields are wrapped in `{}`
+        fields_bytes + 2
+    }
+
+    /// Connect to `ip` instead of resolving the host of [ip-api.com API](https://ip-api.com/)
+    ///
+    /// Useful if DNS is unavailable or the host should be pinned to a specific IP.
+    /// The `Host` header is still set to the original host.
+    /// Other hosts (e.g. [the fallback](Self::set_fallback_base_url)) are resolved as usual
+    pub fn resolve_host_to(mut self, ip: IpAddr) -> Self {
+        self.host_ip = Some(ip);
+        self.client = self.build_client();
+
+        self
+    }
+
+    /// Connect to `address` instead of resolving the host of
+    /// [ip-api.com API](https://ip-api.com/) and using the port of the URL
+    ///
+    /// Unlike [`resolve_host_to`](Self::resolve_host_to), the port is pinned too,
+    /// which is useful for a local caching proxy. `address` is used for connecting only,
+    /// the URL and the `Host` header still point to the original host.
+    /// Other hosts (e.g. [the fallback](Self::set_fallback_base_url)) are connected to as usual
+    pub fn connect_to(mut self, address: SocketAddr) -> Self {
+        self.host_ip = Some(address.ip());
+        self.host_port = Some(address.port());
+        self.client = self.build_client();
+
+        self
+    }
+
+    /// Send requests from the local address `ip`
+    ///
+    /// Useful on multi-homed hosts to choose the interface requests leave through
+    /// (e.g. to request information about the IP of this interface with an empty target).
+    /// The address applies to destinations of the same family only, including the ones
+    /// set via [`connect_to`](Self::connect_to) and [`resolve_host_to`](Self::resolve_host_to).
+    /// Requests fail with [`IpApiError::BindFailed`] if `ip` doesn't belong to this host
+    pub fn bind_to(mut self, ip: IpAddr) -> Self {
+        self.local_ip = Some(ip);
+        self.client = self.build_client();
+
+        self
+    }
+
+    /// Route requests through the proxy set in the environment variables
+    ///
+    /// `http_proxy`/`HTTP_PROXY` (or `https_proxy`/`HTTPS_PROXY` for `https` URLs) are used
+    /// like curl does, hosts listed in `no_proxy`/`NO_PROXY` are connected to directly.
+    /// The variables are read once, when this method is called.
+    ///
+    /// Proxied connections are established to the proxy, so
+    /// [`resolve_host_to`](Self::resolve_host_to) applies only to the hosts excluded
+    /// via `no_proxy`/`NO_PROXY`
+    pub fn use_env_proxy(mut self) -> Self {
+        self.proxy = Some(EnvProxy::from_env());
+        self.client = self.build_client();
+
+        self
+    }
+
+    /// Connect to [ip-api.com API](https://ip-api.com/) only over IPv6
+    ///
+    /// Useful on dual-stack networks to request information about your IPv6 address
+    /// (with an empty target), since the API describes the address the connection came from.
+    /// Requests fail with [`IpApiError::Ipv6Unavailable`] if no IPv6 address is available.
+    /// Has no effect on the IP set via [`resolve_host_to`](Self::resolve_host_to)
+    pub fn prefer_ipv6(mut self) -> Self {
+        self.is_ipv6_only = true;
+        self.client = self.build_client();
+
+        self
+    }
+
+    fn prefer_ipv4(mut self) -> Self {
+        self.is_ipv4_only = true;
+        self.client = self.build_client();
+
+        self
+    }
+
+    /// Set timeout for establishing a TCP connection
+    ///
+    /// Only the TCP handshake is limited (DNS resolution isn't), so a slow response
+    /// after the connection is established is not affected.
+    /// The timeout applies to every attempt, including retries
+    /// and [the fallback](Self::set_fallback_base_url).
+    /// It's independent from [the read timeout](Self::set_read_timeout), which starts once
+    /// the headers are received. To limit the whole request (including DNS resolution),
+    /// wrap the call in [`tokio::time::timeout`], the connect timeout then only fires earlier
+    /// if the handshake itself is stuck. There is no connect timeout by default
+    pub fn set_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self.client = self.build_client();
+
+        self
+    }
+
+    /// Cache the results of [`make_request`](Self::make_request) in memory
+    ///
+    /// Results are cached per target, fields and language. When `max_size` results
+    /// are cached, the oldest one is removed to make room for a new one.
+    /// Errors are never cached. The cache is shared by clones of the config
+    pub fn set_cache(mut self, max_size: usize) -> Self {
+        self.cache = Some(Arc::new(Mutex::new(ResponseCache::new(max_size))));
+
+        self
+    }
+
+    /// Set the maximum size of the body of the response in bytes
+    ///
+    /// Guards against a misbehaving endpoint (e.g. [a fallback](Self::set_fallback_base_url))
+    /// returning a gigantic body. Bigger responses lead to [`IpApiError::ResponseTooLarge`].
+    /// With the `compression` feature the limit applies to the decompressed body too,
+    /// and with the `stream` feature to every response received by
+    /// [`make_batch_stream`](Self::make_batch_stream) (one per 100 targets).
+    /// The default is 10 MiB
+    pub fn set_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+
+        self
+    }
+
+    /// Write a single-line JSON log entry per request to `sink`
+    ///
+    /// Every entry contains `target` (`null` for batch requests), `fields` (the number of
+    /// requested fields), `status` (the HTTP status, `null` if there was no response),
+    /// `latency_ms` (from sending the request until receiving the response headers)
+    /// and `outcome` (`ok`, `rate_limited`, `http_error` or `connection_error`), e.g.
+    /// `{"fields":2,"latency_ms":42,"outcome":"ok","status":200,"target":"1.1.1.1"}`.
+    /// The received [`IpData`] is never logged. Retries are logged as separate requests
+    #[cfg(feature = "json-logs")]
+    pub fn set_log_sink(mut self, sink: impl io::Write + Send + 'static) -> Self {
+        self.log_sink = Some(LogSink(Arc::new(Mutex::new(sink))));
+
+        self
+    }
+
+    /// Pace requests to match the limits of [ip-api.com API](https://ip-api.com/docs/api:json)
+    ///
+    /// The times of the last 45 requests to `/json` (or the last 15 requests to `/batch`,
+    /// which has a lower limit) are recorded, and if all of them are within
+    /// the last 60 seconds, the next request waits until the oldest one leaves the window.
+    /// Both endpoints are paced separately. Every HTTP request counts, including retries
+    /// and each chunk of a batch request.
+    /// The recorded times are shared by clones of the config, so all of them are paced together
+    pub fn with_request_pacing(mut self) -> Self {
+        self.pacing = Some(Arc::new(Pacing::new(PACING_WINDOW)));
+
+        self
+    }
+
+    /// Allow at most `max_requests` requests to be sent, after that every request fails
+    /// with [`IpApiError::QuotaExceeded`] without being sent
+    ///
+    /// A safety limit against an accidentally huge input, unlike
+    /// [`with_request_pacing`](Self::with_request_pacing) it stops instead of waiting.
+    /// Every HTTP request counts, so each chunk of a batch request (up to 100 targets)
+    /// is one request, and so is each retry.
+    /// The count is shared by clones of the config, so the limit is for all of them together
+    pub fn set_request_quota(mut self, max_requests: u32) -> Self {
+        self.quota = Some(RequestQuota {
+            max_requests,
+            used: Arc::new(Mutex::new(0)),
+        });
+
+        self
+    }
+
+    /// Set timeout for downloading the body of the response
+    ///
+    /// Guards against a server that sends the headers and then stalls,
+    /// which matters most for big batch responses. The timeout starts once the headers
+    /// are received and covers only the body, so it's independent from
+    /// [the connect timeout](Self::set_connect_timeout). To limit the whole request,
+    /// wrap the call in [`tokio::time::timeout`], the read timeout then only fires earlier
+    /// if the body itself is stuck.
+    /// Returns [`IpApiError::Timeout`] on expiry. There is no read timeout by default
+    pub fn set_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+
+        self
+    }
 }
 
 /// Create an empty config to create your own from scratch
@@ -968,9 +7174,37 @@ pub fn generate_empty_config() -> IpApiConfig {
         is_hosting_included: false,
         is_query_included: false,
         language: IpApiLanguage::En,
+        is_strict: false,
+        retry_budget: None,
+        backoff: Backoff::Fixed(DEFAULT_RETRY_WAIT),
+        batch_invalid_policy: BatchInvalidPolicy::Passthrough,
+        fallback_base_url: None,
+        connect_timeout: None,
+        read_timeout: None,
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        cache: None,
+        pacing: None,
+        quota: None,
+        #[cfg(feature = "json-logs")]
+        log_sink: None,
+        host_ip: None,
+        host_port: None,
+        local_ip: None,
+        proxy: None,
+        is_ipv4_only: false,
+        is_ipv6_only: false,
+        client: Client::builder().build(Connector::new(false, false)),
     }
 }
 
+/// Generate the lightest config that includes only
+/// [`query`](struct.IpData.html#structfield.query)
+///
+/// Useful for checking that an IP is known to the API while transferring almost no data
+pub fn generate_query_only_config() -> IpApiConfig {
+    generate_empty_config().include_query()
+}
+
 /// Generate minimum config that includes only important fields
 pub fn generate_minimum_config() -> IpApiConfig {
     IpApiConfig {
@@ -999,6 +7233,26 @@ pub fn generate_minimum_config() -> IpApiConfig {
         is_hosting_included: false,
         is_query_included: false,
         language: IpApiLanguage::En,
+        is_strict: false,
+        retry_budget: None,
+        backoff: Backoff::Fixed(DEFAULT_RETRY_WAIT),
+        batch_invalid_policy: BatchInvalidPolicy::Passthrough,
+        fallback_base_url: None,
+        connect_timeout: None,
+        read_timeout: None,
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        cache: None,
+        pacing: None,
+        quota: None,
+        #[cfg(feature = "json-logs")]
+        log_sink: None,
+        host_ip: None,
+        host_port: None,
+        local_ip: None,
+        proxy: None,
+        is_ipv4_only: false,
+        is_ipv6_only: false,
+        client: Client::builder().build(Connector::new(false, false)),
     }
 }
 
@@ -1030,5 +7284,233 @@ pub fn generate_maximum_config() -> IpApiConfig {
         is_hosting_included: true,
         is_query_included: true,
         language: IpApiLanguage::En,
+        is_strict: false,
+        retry_budget: None,
+        backoff: Backoff::Fixed(DEFAULT_RETRY_WAIT),
+        batch_invalid_policy: BatchInvalidPolicy::Passthrough,
+        fallback_base_url: None,
+        connect_timeout: None,
+        read_timeout: None,
+        max_response_bytes: MAX_RESPONSE_BYTES,
+        cache: None,
+        pacing: None,
+        quota: None,
+        #[cfg(feature = "json-logs")]
+        log_sink: None,
+        host_ip: None,
+        host_port: None,
+        local_ip: None,
+        proxy: None,
+        is_ipv4_only: false,
+        is_ipv6_only: false,
+        client: Client::builder().build(Connector::new(false, false)),
+    }
+}
+
+fn shared_config() -> IpApiConfig {
+    static CONFIG: OnceLock<IpApiConfig> = OnceLock::new();
+
+    CONFIG.get_or_init(generate_maximum_config).clone()
+}
+
+/// Request all available information about `target`
+///
+/// Uses a shared maximum config (and its HTTP client), so there is no need to create your own.
+/// `target` can be "ip"/"domain"/"empty string (if you want to request your ip)"
+pub async fn lookup(target: &str) -> Result<IpData, IpApiError> {
+    shared_config().make_request(target).await
+}
+
+/// Request all available information about several `targets` in one batch request
+///
+/// Uses a shared maximum config (and its HTTP client), so there is no need to create your own.
+/// `targets` can be "IPv4"/"IPv6"
+pub async fn lookup_batch(targets: Vec<&str>) -> Result<Vec<IpData>, IpApiError> {
+    shared_config().make_batch_request(targets).await
+}
+
+static MY_IP: Mutex<Option<IpAddr>> = Mutex::new(None);
+
+/// Get the public IP address of this host
+///
+/// The address is requested once (only [`query`](struct.IpData.html#structfield.query)
+/// is included) and cached for the lifetime of the process.
+///
+/// # Notice
+///
+/// The cached address goes stale if the public IP of the host changes,
+/// use [`my_ip_refresh`] to request it again
+pub async fn my_ip() -> Result<IpAddr, IpApiError> {
+    if let Some(ip) = *MY_IP.lock().unwrap_or_else(|error| error.into_inner()) {
+        return Ok(ip);
+    }
+
+    my_ip_refresh().await
+}
+
+/// Request the public IP address of this host again and update the cache of [`my_ip`]
+pub async fn my_ip_refresh() -> Result<IpAddr, IpApiError> {
+    let ip_data = IpApiConfig::with_only(IpDataField::Query)
+        .make_request("")
+        .await?;
+
+    let Some(Ok(ip)) = ip_data.query.map(|query| query.parse::<IpAddr>()) else {
+        return Err(IpApiError::UnexpectedError(Some(
+            "Failed to parse `query` from the response".into(),
+        )));
+    };
+
+    *MY_IP.lock().unwrap_or_else(|error| error.into_inner()) = Some(ip);
+
+    Ok(ip)
+}
+
+/// Request all available information about both public IP addresses of this host
+///
+/// Makes two requests in parallel, one connected over IPv4 and one over IPv6
+/// (see [`IpApiConfig::prefer_ipv6`]), since the API describes the address
+/// the connection came from. A request that fails to connect
+/// ([`IpApiError::ConnectionFailed`] or [`IpApiError::Ipv6Unavailable`], e.g. the network
+/// has no IPv6) yields `None`, other errors (including failures to read or parse
+/// the response) are returned
+pub async fn my_ips() -> Result<MyIps, IpApiError> {
+    let (v4, v6) = tokio::join!(
+        generate_maximum_config().prefer_ipv4().make_request(""),
+        generate_maximum_config().prefer_ipv6().make_request(""),
+    );
+
+    Ok(MyIps {
+        v4: connected_or_none(v4)?,
+        v6: connected_or_none(v6)?,
+    })
+}
+
+/// Turn failures to connect (including a missing address family) into `None`
+fn connected_or_none(result: Result<IpData, IpApiError>) -> Result<Option<IpData>, IpApiError> {
+    match result {
+        Ok(ip_data) => Ok(Some(ip_data)),
+        Err(IpApiError::ConnectionFailed | IpApiError::Ipv6Unavailable) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Detect what kind of target `target` is without making a request
+///
+/// IP addresses are checked against the private and reserved ranges the API rejects.
+/// Anything else must look like a domain name: at least two dot-separated labels
+/// of letters, digits and hyphens (not at the start or end of a label),
+/// a trailing dot is allowed.
+/// Mistyped IPv4 addresses (e.g. `1.1.1.one`) are [`QueryKind::Invalid`].
+///
+/// # Notice
+///
+/// An empty `target` (the IP of this host) is [`QueryKind::Invalid`],
+/// since there is nothing to check
+///
+/// # Example
+///
+/// ```rust
+/// use ip_api_client::{is_valid_query, QueryKind};
+///
+/// assert_eq!(is_valid_query("1.1.1.1"), QueryKind::Ipv4);
+/// assert_eq!(is_valid_query("192.168.1.1"), QueryKind::Private);
+/// assert_eq!(is_valid_query("1.1.1.one"), QueryKind::Invalid);
+/// ```
+pub fn is_valid_query(target: &str) -> QueryKind {
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        return match IpApiConfig::check_ip_range(target) {
+            Err(IpApiError::PrivateRange) => QueryKind::Private,
+            Err(_) => QueryKind::Reserved,
+            Ok(()) if ip.is_ipv4() => QueryKind::Ipv4,
+            Ok(()) => QueryKind::Ipv6,
+        };
+    }
+
+    if !IpApiConfig::is_domain(target) {
+        return QueryKind::Invalid;
+    }
+
+    QueryKind::Domain
+}
+
+/// Sort `data` by [`country`](struct.IpData.html#structfield.country) in place
+///
+/// Entries without the country are placed at the end
+pub fn sort_by_country(data: &mut [IpData]) {
+    data.sort_by(|a, b| match (&a.country, &b.country) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    });
+}
+
+/// Sort `data` by the distance from `origin` in place, the nearest first
+///
+/// Entries without the coordinates are placed at the end
+pub fn sort_by_distance_from(data: &mut [IpData], origin: Coordinates) {
+    data.sort_by(|a, b| {
+        let distance = |ip_data: &IpData| {
+            ip_data
+                .coordinates()
+                .map(|coordinates| origin.distance_km(&coordinates))
+        };
+
+        match (distance(a), distance(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    });
+}
+
+/// Build a [GeoJSON](https://geojson.org/) `FeatureCollection` from `data`
+///
+/// Contains one `Feature` per [`IpData`] (see [`IpData::to_geojson`]),
+/// entries without the coordinates are skipped
+pub fn to_geojson_collection(data: &[IpData]) -> serde_json::Value {
+    let features: Vec<_> = data.iter().filter_map(IpData::to_geojson).collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// Export `data` to CSV
+///
+/// The first row contains the field names (see [`IpDataField::name`]),
+/// followed by one row per [`IpData`] in the order of [`IpData::iter`].
+/// Missing fields are left empty, values with commas, quotes or line breaks are quoted
+pub fn to_csv(data: &[IpData]) -> String {
+    fn escape(value: String) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    }
+
+    let header = IpDataField::ALL
+        .iter()
+        .filter(|field| **field != IpDataField::Message)
+        .map(|field| field.name())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut csv = header + "\n";
+
+    for ip_data in data {
+        let row = ip_data
+            .fields()
+            .into_iter()
+            .map(|(_, value)| {
+                value
+                    .map(|value| escape(value.to_string()))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        csv.push_str(&row);
+        csv.push('\n');
     }
+
+    csv
 }